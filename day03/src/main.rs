@@ -1,18 +1,55 @@
+use std::env;
+use std::fmt;
 use std::io::{self, Read};
 use std::ops::AddAssign;
 
+const DEFAULT_TILES: &str = ".#";
+
+const DEFAULT_TREE: char = '#';
+
+const SLOPES: [(usize, usize); 5] = [(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)];
+
 #[derive(Clone, Copy)]
 struct Point(usize, usize);
 
 struct Hill<'a> {
     pattern: Vec<&'a str>,
+    /// The characters a tile of `pattern` may be, in the order they were configured
+    tiles: Vec<char>,
+    /// The tile toboggans hit trees on, which is always one of `tiles`
+    tree: char,
     width: usize,
 }
 
+/// An error raised while parsing a `Hill`, where rows and columns are 1-indexed
+#[derive(Debug)]
+enum ParseHillError {
+    Empty,
+    InvalidWidth {
+        row: usize,
+        expected: usize,
+        found: usize,
+    },
+    NonAsciiTile(char),
+    /// The tree tile is not one of the tiles the hill may be made of
+    UnknownTreeTile(char),
+    UnknownTile {
+        row: usize,
+        column: usize,
+        tile: char,
+    },
+}
+
 struct Toboggan {
     slope: Point,
 }
 
+/// The cheapest way down a `Hill`, visiting one `Point` per row
+struct TreePath {
+    points: Vec<Point>,
+    trees_hit: usize,
+}
+
 struct TobogganRide<'r, 'h> {
     toboggan: &'r Toboggan,
     cursor: Point,
@@ -38,12 +75,126 @@ impl From<&(usize, usize)> for Point {
     }
 }
 
-impl<'h> FromIterator<&'h str> for Hill<'h> {
-    fn from_iter<T: IntoIterator<Item = &'h str>>(iter: T) -> Self {
-        let pattern = iter.into_iter().collect::<Vec<_>>();
-        let width = pattern[0].len();
+impl fmt::Display for ParseHillError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "hill has no tiles"),
+            Self::InvalidWidth {
+                row,
+                expected,
+                found,
+            } => write!(f, "row {row} has width {found}, expected {expected}"),
+            Self::NonAsciiTile(tile) => write!(f, "tile '{tile}' is not an ASCII character"),
+            Self::UnknownTreeTile(tree) => write!(f, "tree tile '{tree}' is not one of the tiles"),
+            Self::UnknownTile { row, column, tile } => {
+                write!(f, "unknown tile '{tile}' at row {row}, column {column}")
+            }
+        }
+    }
+}
+
+impl<'h> Hill<'h> {
+    /// Parses `input` into a hill whose tiles must all be one of the ASCII characters in `tiles`
+    /// and whose rows must all share the width of the first row, with trees on `tree` tiles
+    fn parse(input: &'h str, tiles: &str, tree: char) -> Result<Self, ParseHillError> {
+        use ParseHillError::*;
+
+        if let Some(tile) = tiles.chars().find(|c| !c.is_ascii()) {
+            return Err(NonAsciiTile(tile));
+        }
+
+        let tiles = tiles.chars().fold(Vec::new(), |mut tiles, tile| {
+            if !tiles.contains(&tile) {
+                tiles.push(tile);
+            }
+            tiles
+        });
+        if !tiles.contains(&tree) {
+            return Err(UnknownTreeTile(tree));
+        }
+
+        let pattern = input.lines().collect::<Vec<_>>();
+        let width = pattern.first().ok_or(Empty)?.len();
+        if width == 0 {
+            return Err(Empty);
+        }
+
+        for (row, line) in pattern.iter().enumerate() {
+            if let Some((column, tile)) = line.chars().enumerate().find(|(_, c)| !tiles.contains(c))
+            {
+                return Err(UnknownTile {
+                    row: row + 1,
+                    column: column + 1,
+                    tile,
+                });
+            }
+
+            if line.len() != width {
+                return Err(InvalidWidth {
+                    row: row + 1,
+                    expected: width,
+                    found: line.len(),
+                });
+            }
+        }
+
+        Ok(Self {
+            pattern,
+            tiles,
+            tree,
+            width,
+        })
+    }
+
+    fn is_tree(&self, point: Point) -> bool {
+        self.pattern
+            .get(point.1)
+            .and_then(|row| row.get(point.0..(point.0 + 1)))
+            .is_some_and(|tile| self.is_tree_tile(tile))
+    }
+
+    fn is_tree_tile(&self, tile: &str) -> bool {
+        tile.starts_with(self.tree)
+    }
+
+    /// Finds the path from the top row to the bottom row that hits the fewest trees, where each
+    /// step moves down one row and left or right by at most `reach` columns (wrapping around the
+    /// hill's edges)
+    fn min_tree_path(&self, reach: usize) -> Option<TreePath> {
+        let width = self.width as isize;
+        let reach = reach.min(self.width) as isize;
+
+        // `trees[x]` holds the fewest trees hit by any path ending at column `x` of the current
+        // row, while `parents[y][x]` holds the column that path came from in row `y - 1`
+        let mut trees = (0..self.width)
+            .map(|x| usize::from(self.is_tree(Point(x, 0))))
+            .collect::<Vec<_>>();
+        let mut parents: Vec<Vec<usize>> = Vec::with_capacity(self.pattern.len());
+
+        for y in 1..self.pattern.len() {
+            let (next_trees, row_parents) = (0..self.width)
+                .map(|x| {
+                    let (fewest, parent) = (-reach..=reach)
+                        .map(|dx| (x as isize + dx).rem_euclid(width) as usize)
+                        .map(|px| (trees[px], px))
+                        .min()
+                        .expect("`reach` range is never empty");
+                    (fewest + usize::from(self.is_tree(Point(x, y))), parent)
+                })
+                .unzip();
+            trees = next_trees;
+            parents.push(row_parents);
+        }
+
+        let (trees_hit, mut x) = trees.into_iter().zip(0..).min()?;
+        let mut points = vec![Point(x, parents.len())];
+        for (y, row_parents) in parents.iter().enumerate().rev() {
+            x = row_parents[x];
+            points.push(Point(x, y));
+        }
+        points.reverse();
 
-        Self { pattern, width }
+        Some(TreePath { points, trees_hit })
     }
 }
 
@@ -67,6 +218,19 @@ impl<'r, 'h> TobogganRide<'r, 'h> {
             .get(cursor.0..(cursor.0 + 1))
     }
 
+    /// Consumes the ride, counting how many times each of the hill's tiles is hit
+    fn tile_counts(self) -> Vec<(char, usize)> {
+        let mut counts = self.hill.tiles.iter().map(|t| (*t, 0)).collect::<Vec<_>>();
+
+        for tile in self {
+            if let Some((_, count)) = counts.iter_mut().find(|(t, _)| tile.starts_with(*t)) {
+                *count += 1;
+            }
+        }
+
+        counts
+    }
+
     fn advance(&mut self) {
         self.cursor += self.toboggan.slope;
         self.cursor.normalize_x(self.hill.width);
@@ -89,22 +253,21 @@ fn part_one(hill: &Hill<'_>) {
     let origin = Point(0, 0);
     let trees_hit = toboggan
         .ride(origin, hill)
-        .filter(|tile| *tile == "#")
+        .filter(|tile| hill.is_tree_tile(tile))
         .count();
 
     println!("Part One: {trees_hit}");
 }
 
 fn part_two(hill: &Hill<'_>) {
-    let slopes = [(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)];
     let origin = Point(0, 0);
-    let tree_product: usize = slopes
+    let tree_product: usize = SLOPES
         .iter()
         .map(|s| {
             let toboggan = Toboggan { slope: s.into() };
             toboggan
                 .ride(origin, hill)
-                .filter(|tile| *tile == "#")
+                .filter(|tile| hill.is_tree_tile(tile))
                 .count()
         })
         .product();
@@ -112,14 +275,175 @@ fn part_two(hill: &Hill<'_>) {
     println!("Part Two: {tree_product}");
 }
 
+fn tile_report(hill: &Hill<'_>) {
+    let origin = Point(0, 0);
+
+    for slope in SLOPES.iter() {
+        let toboggan = Toboggan {
+            slope: slope.into(),
+        };
+        let counts = toboggan
+            .ride(origin, hill)
+            .tile_counts()
+            .into_iter()
+            .map(|(tile, count)| format!("'{tile}': {count}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        println!("Tiles {slope:?}: {counts}");
+    }
+}
+
+fn min_tree_path(hill: &Hill<'_>, reach: usize) {
+    match hill.min_tree_path(reach) {
+        Some(path) => {
+            let columns = path
+                .points
+                .iter()
+                .map(|p| p.0.to_string())
+                .collect::<Vec<_>>()
+                .join(" ");
+            println!(
+                "Min-Tree Path (reach {reach}): {} [{columns}]",
+                path.trees_hit
+            );
+        }
+        None => println!("Min-Tree Path (reach {reach}): None"),
+    }
+}
+
+/// `[--tiles <chars>] [--tree <char>] [--report] [--min-path <reach>]`
+struct Options {
+    /// Every character a tile of the hill may be
+    tiles: String,
+    /// The tile trees stand on
+    tree: char,
+    /// Whether to print how often each slope hits each tile
+    report: bool,
+    /// When set, the path hitting the fewest trees is printed for toboggans with this reach
+    min_path: Option<usize>,
+}
+
+impl Options {
+    fn from_args() -> io::Result<Self> {
+        let mut options = Self {
+            tiles: DEFAULT_TILES.to_string(),
+            tree: DEFAULT_TREE,
+            report: false,
+            min_path: None,
+        };
+        let mut args = env::args().skip(1);
+        let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidInput, msg);
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--tiles" => {
+                    options.tiles = args
+                        .next()
+                        .ok_or_else(|| invalid(format!("Expected tiles after {arg}")))?;
+                }
+                "--tree" => {
+                    let tree = args.next().and_then(|tree| {
+                        let mut chars = tree.chars();
+                        chars.next().filter(|_| chars.next().is_none())
+                    });
+                    options.tree =
+                        tree.ok_or_else(|| invalid(format!("Expected one tile after {arg}")))?;
+                }
+                "--report" => options.report = true,
+                "--min-path" => {
+                    let reach = args.next().and_then(|reach| reach.parse().ok());
+                    options.min_path = Some(reach.ok_or_else(|| {
+                        invalid(format!("Expected a non-negative reach after {arg}"))
+                    })?);
+                }
+                _ => return Err(invalid(format!("Unknown argument `{arg}`"))),
+            }
+        }
+
+        Ok(options)
+    }
+}
+
 fn main() -> io::Result<()> {
+    let options = Options::from_args()?;
+
     let mut input = String::new();
     io::stdin().read_to_string(&mut input)?;
 
-    let hill = input.lines().collect();
+    let hill = Hill::parse(&input, &options.tiles, options.tree)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))?;
 
     part_one(&hill);
     part_two(&hill);
+    if options.report {
+        tile_report(&hill);
+    }
+
+    if let Some(reach) = options.min_path {
+        min_tree_path(&hill, reach);
+    }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn columns(hill: &Hill<'_>, reach: usize) -> (usize, Vec<usize>) {
+        let path = hill.min_tree_path(reach).unwrap();
+        (path.trees_hit, path.points.iter().map(|p| p.0).collect())
+    }
+
+    #[test]
+    fn test_empty_hill() {
+        for input in ["", "\n\n"] {
+            assert!(matches!(
+                Hill::parse(input, DEFAULT_TILES, DEFAULT_TREE),
+                Err(ParseHillError::Empty)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_min_tree_path_wraps() {
+        let hill = Hill::parse(".##\n##.\n#.#", DEFAULT_TILES, DEFAULT_TREE).unwrap();
+        assert_eq!(columns(&hill, 1), (0, vec![0, 2, 1]));
+    }
+
+    #[test]
+    fn test_min_tree_path_reach_zero() {
+        let hill = Hill::parse(".#.\n##.\n#..", DEFAULT_TILES, DEFAULT_TREE).unwrap();
+        assert_eq!(columns(&hill, 0), (0, vec![2, 2, 2]));
+
+        let hill = Hill::parse(".##\n##.\n#.#", DEFAULT_TILES, DEFAULT_TREE).unwrap();
+        assert_eq!(columns(&hill, 0).0, 2);
+    }
+
+    #[test]
+    fn test_min_tree_path_reach_past_width() {
+        let hill = Hill::parse("#.##\n###.\n.###\n##.#", DEFAULT_TILES, DEFAULT_TREE).unwrap();
+        for reach in [2, 4, 100] {
+            assert_eq!(columns(&hill, reach), (0, vec![1, 3, 0, 2]));
+        }
+    }
+
+    #[test]
+    fn test_min_tree_path_follows_parents() {
+        let input = "..#..\n#.#.#\n##..#\n.####\n#.##.";
+        let hill = Hill::parse(input, DEFAULT_TILES, DEFAULT_TREE).unwrap();
+        let reach = 1;
+        let path = hill.min_tree_path(reach).unwrap();
+
+        assert_eq!(path.points.len(), hill.pattern.len());
+        let trees_hit = path.points.iter().filter(|p| hill.is_tree(**p)).count();
+        assert_eq!(trees_hit, path.trees_hit);
+        assert_eq!(path.trees_hit, 1);
+        for (y, pair) in path.points.windows(2).enumerate() {
+            assert_eq!((pair[0].1, pair[1].1), (y, y + 1));
+            let dx = pair[0].0.abs_diff(pair[1].0);
+            assert!(dx.min(hill.width - dx) <= reach);
+        }
+    }
+}
@@ -0,0 +1,172 @@
+use crate::{Document, Passport};
+use serde::Serialize;
+use std::io::{self, Write};
+use std::str::FromStr;
+
+/// The columns written by `Format::Csv`, matching the fields of `ExportRecord`
+const CSV_HEADER: &str = "record,valid,passport_id,country_id,birth_year,issue_year,\
+expiration_year,height,height_unit,height_cm,hair_color,eye_color,errors";
+
+#[derive(Clone, Copy)]
+pub enum Format {
+    /// One JSON object per line
+    Json,
+    Csv,
+}
+
+/// A passport flattened into normalized fields, where any field the passport could not be parsed
+/// into is left empty
+#[derive(Serialize)]
+struct ExportRecord<'a> {
+    record: usize,
+    valid: bool,
+    passport_id: Option<&'a str>,
+    country_id: Option<usize>,
+    birth_year: Option<usize>,
+    issue_year: Option<usize>,
+    expiration_year: Option<usize>,
+    height: Option<usize>,
+    height_unit: Option<&'static str>,
+    /// The height converted into centimeters, whatever unit it was written in
+    height_cm: Option<f64>,
+    hair_color: Option<String>,
+    eye_color: Option<&'static str>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    errors: Vec<String>,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(Self::Json),
+            "csv" => Ok(Self::Csv),
+            _ => Err(format!(
+                "Unknown export format `{s}`, expected `json` or `csv`"
+            )),
+        }
+    }
+}
+
+impl<'a> ExportRecord<'a> {
+    fn new(record: usize, document: &Document<'a>) -> Self {
+        let passport = Passport::try_from(document);
+        let mut errors = document
+            .parse_errors
+            .iter()
+            .map(ToString::to_string)
+            .chain(document.errors.iter().map(ToString::to_string))
+            .collect::<Vec<_>>();
+
+        match passport {
+            Ok(passport) => Self {
+                record,
+                valid: errors.is_empty(),
+                passport_id: Some(passport.id),
+                country_id: passport.country_id.ok(),
+                birth_year: Some(passport.birth_year),
+                issue_year: Some(passport.issue_year),
+                expiration_year: Some(passport.expiration_year),
+                height: Some(passport.height.0),
+                height_unit: Some(passport.height.1.suffix()),
+                height_cm: Some(passport.height.normalized()),
+                hair_color: Some(format!("#{}", passport.hair_color.to_ascii_lowercase())),
+                eye_color: Some(passport.eye_color.name()),
+                errors,
+            },
+            Err(conversion_errors) => {
+                // A schema may accept values the typed passport cannot represent, so conversion
+                // failures are reported alongside the schema's own errors
+                for err in conversion_errors {
+                    let err = err.to_string();
+                    if !errors.contains(&err) {
+                        errors.push(err);
+                    }
+                }
+
+                Self {
+                    record,
+                    valid: false,
+                    passport_id: None,
+                    country_id: None,
+                    birth_year: None,
+                    issue_year: None,
+                    expiration_year: None,
+                    height: None,
+                    height_unit: None,
+                    height_cm: None,
+                    hair_color: None,
+                    eye_color: None,
+                    errors,
+                }
+            }
+        }
+    }
+
+    fn to_csv(&self) -> String {
+        fn cell<T: ToString>(value: &Option<T>) -> String {
+            value.as_ref().map(ToString::to_string).unwrap_or_default()
+        }
+
+        [
+            self.record.to_string(),
+            self.valid.to_string(),
+            cell(&self.passport_id),
+            cell(&self.country_id),
+            cell(&self.birth_year),
+            cell(&self.issue_year),
+            cell(&self.expiration_year),
+            cell(&self.height),
+            cell(&self.height_unit),
+            cell(&self.height_cm),
+            cell(&self.hair_color),
+            cell(&self.eye_color),
+            self.errors.join("; "),
+        ]
+        .iter()
+        .map(|value| escape_csv(value))
+        .collect::<Vec<_>>()
+        .join(",")
+    }
+}
+
+/// Quotes `value` if it contains a character with special meaning in CSV
+fn escape_csv(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Writes every valid passport in `documents` to `out` as `format`, along with every invalid one
+/// and its errors when `include_invalid` is set
+pub fn export<W: Write>(
+    out: &mut W,
+    documents: &[Document<'_>],
+    format: Format,
+    include_invalid: bool,
+) -> io::Result<()> {
+    if let Format::Csv = format {
+        writeln!(out, "{CSV_HEADER}")?;
+    }
+
+    let records = documents
+        .iter()
+        .enumerate()
+        .map(|(i, document)| ExportRecord::new(i + 1, document))
+        .filter(|record| record.valid || include_invalid);
+
+    for record in records {
+        match format {
+            Format::Json => {
+                serde_json::to_writer(&mut *out, &record)?;
+                writeln!(out)?;
+            }
+            Format::Csv => writeln!(out, "{}", record.to_csv())?,
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,103 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::fmt;
+use std::str::FromStr;
+
+lazy_static! {
+    static ref FEET_INCHES_RE: Regex =
+        Regex::new(r#"^(?P<feet>\d+)'(?:(?P<inches>\d+)")?$"#).unwrap();
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DistanceUnit {
+    Millimeters,
+    Centimeters,
+    Inches,
+}
+
+/// A height written as a whole number of some unit, such as `183cm`, `1830mm`, `72in` or `6'0"`
+/// (which is read as `72in`)
+#[derive(Debug)]
+pub struct Height(pub usize, pub DistanceUnit);
+
+#[derive(Debug)]
+pub struct ParseHeightError;
+
+impl DistanceUnit {
+    pub fn suffix(&self) -> &'static str {
+        match self {
+            Self::Millimeters => "mm",
+            Self::Centimeters => "cm",
+            Self::Inches => "in",
+        }
+    }
+
+    fn millimeters(&self) -> f64 {
+        match self {
+            Self::Millimeters => 1.0,
+            Self::Centimeters => 10.0,
+            Self::Inches => 25.4,
+        }
+    }
+}
+
+impl FromStr for DistanceUnit {
+    type Err = ParseHeightError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "mm" => Ok(Self::Millimeters),
+            "cm" => Ok(Self::Centimeters),
+            "in" => Ok(Self::Inches),
+            _ => Err(ParseHeightError),
+        }
+    }
+}
+
+impl Height {
+    /// Converts this height into `unit`, which may leave it fractional
+    pub fn to_unit(&self, unit: DistanceUnit) -> f64 {
+        self.0 as f64 * self.1.millimeters() / unit.millimeters()
+    }
+
+    /// This height in centimeters, rounded to two decimal places, so heights written in different
+    /// units can be compared
+    pub fn normalized(&self) -> f64 {
+        (self.to_unit(DistanceUnit::Centimeters) * 100.0).round() / 100.0
+    }
+}
+
+impl FromStr for Height {
+    type Err = ParseHeightError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(caps) = FEET_INCHES_RE.captures(s) {
+            let feet: usize = caps["feet"].parse().map_err(|_| ParseHeightError)?;
+            let inches: usize = caps
+                .name("inches")
+                .map_or(Ok(0), |inches| inches.as_str().parse())
+                .map_err(|_| ParseHeightError)?;
+
+            return feet
+                .checked_mul(12)
+                .and_then(|total| total.checked_add(inches))
+                .filter(|_| inches < 12)
+                .map(|total| Self(total, DistanceUnit::Inches))
+                .ok_or(ParseHeightError);
+        }
+
+        let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+        let (quantity, unit) = s.split_at(split);
+
+        Ok(Self(
+            quantity.parse().map_err(|_| ParseHeightError)?,
+            unit.parse()?,
+        ))
+    }
+}
+
+impl fmt::Display for Height {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.0, self.1.suffix())
+    }
+}
@@ -1,51 +1,33 @@
+mod export;
+mod height;
+mod schema;
+mod stats;
+
+use export::Format;
+use height::Height;
 use lazy_static::lazy_static;
 use regex::Regex;
+use schema::{Schema, ValidationError};
+use std::collections::hash_map::Entry;
 use std::collections::HashMap;
+use std::env;
+use std::fmt;
+use std::fs;
 use std::io::{self, Read};
 use std::str::FromStr;
 
-lazy_static! {
-    static ref KEY_VALUE_RE: Regex = Regex::new(r"\b(?P<key>\w{3}):(?P<value>#?\w+)").unwrap();
-}
-
-enum DistanceUnit {
-    Inches,
-    Centimeters,
-}
-
-struct Height(usize, DistanceUnit);
+/// The rules applied to passports when no `--schema` is given
+const DEFAULT_SCHEMA: &str = include_str!("../schema/passport.toml");
 
-impl FromStr for Height {
-    type Err = ParsePassportError;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (hgt, units) = s.split_at(s.len() - 2);
-
-        match units {
-            "in" => Ok(Self(
-                hgt.parse()
-                    .ok()
-                    .filter(|hgt| (59..=76).contains(hgt))
-                    .ok_or(Self::Err::InvalidField(Field::Height))?,
-                DistanceUnit::Inches,
-            )),
-            "cm" => Ok(Self(
-                hgt.parse()
-                    .ok()
-                    .filter(|hgt| (150..=193).contains(hgt))
-                    .ok_or(Self::Err::InvalidField(Field::Height))?,
-                DistanceUnit::Centimeters,
-            )),
-            _ => Err(Self::Err::InvalidField(Field::Height)),
-        }
-    }
-}
-
-enum ParsePassportError {
-    MissingField(Field),
-    InvalidField(Field),
+lazy_static! {
+    static ref KEY_VALUE_RE: Regex =
+        Regex::new(r#"\b(?P<key>\w{3}):(?P<value>#?[\w'"]+)"#).unwrap();
+    static ref STRICT_KEY_VALUE_RE: Regex =
+        Regex::new(r#"^(?P<key>\w{3}):(?P<value>#?[\w'"]+)$"#).unwrap();
+    static ref TOKEN_RE: Regex = Regex::new(r"\S+").unwrap();
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
 enum Field {
     PassportID,
     CountryID,
@@ -67,8 +49,66 @@ enum EyeColor {
     Other,
 }
 
+impl Field {
+    const ALL: [Self; 8] = [
+        Self::BirthYear,
+        Self::IssueYear,
+        Self::ExpirationYear,
+        Self::Height,
+        Self::HairColor,
+        Self::EyeColor,
+        Self::PassportID,
+        Self::CountryID,
+    ];
+
+    /// The three letter key identifying this field within a passport record
+    fn key(&self) -> &'static str {
+        match self {
+            Self::PassportID => "pid",
+            Self::CountryID => "cid",
+            Self::BirthYear => "byr",
+            Self::ExpirationYear => "eyr",
+            Self::IssueYear => "iyr",
+            Self::Height => "hgt",
+            Self::HairColor => "hcl",
+            Self::EyeColor => "ecl",
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::PassportID => "Passport ID",
+            Self::CountryID => "Country ID",
+            Self::BirthYear => "Birth Year",
+            Self::ExpirationYear => "Expiration Year",
+            Self::IssueYear => "Issue Year",
+            Self::Height => "Height",
+            Self::HairColor => "Hair Color",
+            Self::EyeColor => "Eye Color",
+        }
+    }
+
+    fn invalid(&self, value: &str) -> ValidationError {
+        ValidationError::invalid(self.key(), self.name(), value)
+    }
+}
+
+impl EyeColor {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Amber => "Amber",
+            Self::Blue => "Blue",
+            Self::Brown => "Brown",
+            Self::Gray => "Gray",
+            Self::Green => "Green",
+            Self::Hazel => "Hazel",
+            Self::Other => "Other",
+        }
+    }
+}
+
 impl FromStr for EyeColor {
-    type Err = ParsePassportError;
+    type Err = ValidationError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
@@ -79,15 +119,88 @@ impl FromStr for EyeColor {
             "grn" => Ok(Self::Green),
             "hzl" => Ok(Self::Hazel),
             "oth" => Ok(Self::Other),
-            _ => Err(Self::Err::InvalidField(Field::EyeColor)),
+            _ => Err(Field::EyeColor.invalid(s)),
         }
     }
 }
 
-#[allow(dead_code)]
+/// A problem with the `key:value` tokens of a record, reported only in `--strict` mode, where each
+/// `offset` is the byte offset of the offending token within its record
+#[derive(Debug)]
+enum ParseRecordError<'a> {
+    UnknownKey {
+        key: &'a str,
+        offset: usize,
+    },
+    DuplicateKey {
+        key: &'a str,
+        offset: usize,
+        first_offset: usize,
+    },
+    MalformedToken {
+        token: &'a str,
+        offset: usize,
+    },
+}
+
+impl fmt::Display for ParseRecordError<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownKey { key, offset } => write!(f, "unknown key `{key}` at byte {offset}"),
+            Self::DuplicateKey {
+                key,
+                offset,
+                first_offset,
+            } => write!(
+                f,
+                "duplicate key `{key}` at byte {offset} (first seen at byte {first_offset})"
+            ),
+            Self::MalformedToken { token, offset } => {
+                write!(f, "malformed token `{token}` at byte {offset}")
+            }
+        }
+    }
+}
+
+/// Checks every whitespace separated token of `record` is a well-formed `key:value` pair whose key
+/// is known to `schema` and appears only once
+fn parse_strict<'a>(record: &'a str, schema: &Schema) -> Vec<ParseRecordError<'a>> {
+    let mut first_offsets = HashMap::new();
+
+    TOKEN_RE
+        .find_iter(record)
+        .filter_map(|token| {
+            let offset = token.start();
+            let key = match STRICT_KEY_VALUE_RE.captures(token.as_str()) {
+                Some(caps) => caps.name("key").unwrap().as_str(),
+                None => {
+                    return Some(ParseRecordError::MalformedToken {
+                        token: token.as_str(),
+                        offset,
+                    })
+                }
+            };
+
+            match first_offsets.entry(key) {
+                Entry::Occupied(first_offset) => Some(ParseRecordError::DuplicateKey {
+                    key,
+                    offset,
+                    first_offset: *first_offset.get(),
+                }),
+                Entry::Vacant(first_offset) => {
+                    first_offset.insert(offset);
+                    (!schema.has_key(key)).then_some(ParseRecordError::UnknownKey { key, offset })
+                }
+            }
+        })
+        .collect()
+}
+
+/// A typed view of a passport record, where range checks are left to the record's `Schema`
 struct Passport<'a> {
-    id: usize,
-    country_id: Result<usize, ParsePassportError>,
+    /// Kept as written so leading zeros survive
+    id: &'a str,
+    country_id: Result<usize, ValidationError>,
     birth_year: usize,
     expiration_year: usize,
     issue_year: usize,
@@ -96,137 +209,253 @@ struct Passport<'a> {
     eye_color: EyeColor,
 }
 
-impl<'a> TryFrom<HashMap<&'a str, &'a str>> for Passport<'a> {
-    type Error = ParsePassportError;
-
-    fn try_from(fields: HashMap<&'a str, &'a str>) -> Result<Self, Self::Error> {
-        let id = fields
-            .get("pid")
-            .ok_or(Self::Error::MissingField(Field::PassportID))
-            .map(|pid| {
-                (pid.len() == 9)
-                    .then(|| pid.parse().ok())
-                    .flatten()
-                    .ok_or(Self::Error::InvalidField(Field::PassportID))
-            })?;
-
-        let country_id = fields
-            .get("cid")
-            .ok_or(Self::Error::MissingField(Field::CountryID))
-            .map(|cid| {
-                cid.parse()
-                    .map_err(|_| Self::Error::InvalidField(Field::CountryID))
-            });
-
-        let birth_year = fields
-            .get("byr")
-            .ok_or(Self::Error::MissingField(Field::BirthYear))
-            .map(|byr| {
-                (byr.len() == 4)
-                    .then(|| byr.parse().ok().filter(|y| (1920..=2002).contains(y)))
-                    .flatten()
-                    .ok_or(Self::Error::InvalidField(Field::BirthYear))
-            })?;
-
-        let expiration_year = fields
-            .get("eyr")
-            .ok_or(Self::Error::MissingField(Field::ExpirationYear))
-            .map(|eyr| {
-                (eyr.len() == 4)
-                    .then(|| eyr.parse().ok().filter(|y| (2020..=2030).contains(y)))
-                    .flatten()
-                    .ok_or(Self::Error::InvalidField(Field::ExpirationYear))
-            })?;
-
-        let issue_year = fields
-            .get("iyr")
-            .ok_or(Self::Error::MissingField(Field::IssueYear))
-            .map(|iyr| {
-                (iyr.len() == 4)
-                    .then(|| iyr.parse().ok().filter(|y| (2010..=2020).contains(y)))
-                    .flatten()
-                    .ok_or(Self::Error::InvalidField(Field::IssueYear))
-            })?;
-
-        let height = fields
-            .get("hgt")
-            .ok_or(Self::Error::MissingField(Field::Height))?
-            .parse();
-
-        let hair_color = fields
-            .get("hcl")
-            .ok_or(Self::Error::MissingField(Field::HairColor))
-            .map(|hcl| {
-                let (prefix, hex) = hcl.split_at(1);
-                if prefix == "#" && hex.len() == 6 && hex.chars().all(|c| c.is_digit(16)) {
-                    Ok(hex)
-                } else {
-                    Err(Self::Error::InvalidField(Field::HairColor))
-                }
-            })?;
-
-        let eye_color = fields
-            .get("ecl")
-            .ok_or(Self::Error::MissingField(Field::EyeColor))?
-            .parse();
-
-        Ok(Self {
-            id: id?,
-            country_id: match country_id {
-                Ok(cid) => cid,
-                Err(err) => Err(err),
-            },
-            birth_year: birth_year?,
-            expiration_year: expiration_year?,
-            issue_year: issue_year?,
-            height: height?,
-            hair_color: hair_color?,
-            eye_color: eye_color?,
-        })
-    }
+/// A record's `key:value` fields along with every problem found validating them against a
+/// `Schema`
+struct Document<'a> {
+    /// The 1-indexed line of the input the record starts on
+    line: usize,
+    fields: HashMap<&'a str, &'a str>,
+    errors: Vec<ValidationError>,
+    /// Problems with the record's tokens, which are only looked for in `--strict` mode
+    parse_errors: Vec<ParseRecordError<'a>>,
 }
 
-impl<'a> TryFrom<&'a str> for Passport<'a> {
-    type Error = ParsePassportError;
-
-    fn try_from(passport: &'a str) -> Result<Self, Self::Error> {
-        KEY_VALUE_RE
-            .captures_iter(passport)
+impl<'a> Document<'a> {
+    fn new(line: usize, record: &'a str, schema: &Schema, strict: bool) -> Self {
+        let fields = KEY_VALUE_RE
+            .captures_iter(record)
             .map(|caps| {
                 let key = caps.name("key").unwrap().as_str();
                 let value = caps.name("value").unwrap().as_str();
                 (key, value)
             })
-            .collect::<HashMap<&'a str, &'a str>>()
-            .try_into()
+            .collect();
+        let errors = schema.validate(&fields);
+        let parse_errors = if strict {
+            parse_strict(record, schema)
+        } else {
+            Vec::new()
+        };
+
+        Self {
+            line,
+            fields,
+            errors,
+            parse_errors,
+        }
+    }
+
+    fn is_valid(&self) -> bool {
+        self.errors.is_empty() && self.parse_errors.is_empty()
     }
 }
 
-fn part_one<'a>(passports: &[Result<Passport<'a>, ParsePassportError>]) {
-    let answer = passports
+impl<'a> TryFrom<&Document<'a>> for Passport<'a> {
+    /// Every field that could not be converted into its typed representation
+    type Error = Vec<ValidationError>;
+
+    fn try_from(document: &Document<'a>) -> Result<Self, Self::Error> {
+        let get = |field: Field| {
+            document
+                .fields
+                .get(field.key())
+                .copied()
+                .ok_or_else(|| ValidationError::missing(field.key(), field.name()))
+        };
+        let number = |field: Field| {
+            get(field).and_then(|value| value.parse().map_err(|_| field.invalid(value)))
+        };
+
+        let id = get(Field::PassportID);
+        let country_id = number(Field::CountryID);
+        let birth_year = number(Field::BirthYear);
+        let expiration_year = number(Field::ExpirationYear);
+        let issue_year = number(Field::IssueYear);
+        let height =
+            get(Field::Height).and_then(|hgt| hgt.parse().map_err(|_| Field::Height.invalid(hgt)));
+        let hair_color = get(Field::HairColor).and_then(|hcl| {
+            hcl.strip_prefix('#')
+                .ok_or_else(|| Field::HairColor.invalid(hcl))
+        });
+        let eye_color = get(Field::EyeColor).and_then(str::parse);
+
+        match (
+            id,
+            birth_year,
+            expiration_year,
+            issue_year,
+            height,
+            hair_color,
+            eye_color,
+        ) {
+            (
+                Ok(id),
+                Ok(birth_year),
+                Ok(expiration_year),
+                Ok(issue_year),
+                Ok(height),
+                Ok(hair_color),
+                Ok(eye_color),
+            ) => Ok(Self {
+                id,
+                country_id,
+                birth_year,
+                expiration_year,
+                issue_year,
+                height,
+                hair_color,
+                eye_color,
+            }),
+            (id, birth_year, expiration_year, issue_year, height, hair_color, eye_color) => Err([
+                birth_year.err(),
+                issue_year.err(),
+                expiration_year.err(),
+                height.err(),
+                hair_color.err(),
+                eye_color.err(),
+                id.err(),
+            ]
+            .into_iter()
+            .flatten()
+            .collect()),
+        }
+    }
+}
+
+/// Command line options, given as
+/// `[--report] [--stats] [--strict] [--schema <path>] [--export <json|csv> [--include-invalid]]`
+#[derive(Default)]
+struct Options {
+    report: bool,
+    stats: bool,
+    strict: bool,
+    schema: Option<String>,
+    /// When set, passports are written to stdout in this format instead of the puzzle answers
+    export: Option<Format>,
+    include_invalid: bool,
+}
+
+impl Options {
+    fn from_args() -> io::Result<Self> {
+        let mut options = Self::default();
+        let mut args = env::args().skip(1);
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--report" => options.report = true,
+                "--stats" => options.stats = true,
+                "--strict" => options.strict = true,
+                "--schema" => {
+                    let path = args.next().ok_or_else(|| {
+                        io::Error::new(
+                            io::ErrorKind::InvalidInput,
+                            format!("Expected a schema path after {arg}"),
+                        )
+                    })?;
+                    options.schema = Some(path);
+                }
+                "--export" => {
+                    let format = args.next().unwrap_or_default();
+                    options.export = Some(
+                        format
+                            .parse()
+                            .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?,
+                    );
+                }
+                "--include-invalid" => options.include_invalid = true,
+                _ => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!("Unknown argument `{arg}`"),
+                    ))
+                }
+            }
+        }
+
+        Ok(options)
+    }
+
+    fn schema(&self) -> io::Result<Schema> {
+        let schema = match &self.schema {
+            Some(path) => fs::read_to_string(path)?,
+            None => DEFAULT_SCHEMA.to_string(),
+        };
+
+        schema
+            .parse()
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+}
+
+fn part_one(documents: &[Document<'_>]) {
+    let answer = documents
         .iter()
-        .filter(|p| !matches!(p, Err(ParsePassportError::MissingField(_))))
+        .filter(|document| {
+            !document
+                .errors
+                .iter()
+                .any(|err| matches!(err, ValidationError::MissingField { .. }))
+        })
         .count();
 
     println!("Part One: {answer}");
 }
 
-fn part_two<'a>(passports: &[Result<Passport<'a>, ParsePassportError>]) {
-    let answer = passports.iter().filter(|res| res.is_ok()).count();
+fn part_two(documents: &[Document<'_>]) {
+    let answer = documents.iter().filter(|d| d.is_valid()).count();
 
     println!("Part Two: {answer}");
 }
 
+fn report(documents: &[Document<'_>]) {
+    for (i, document) in documents.iter().enumerate() {
+        let record = format!("Record {} (line {})", i + 1, document.line);
+
+        if document.is_valid() {
+            println!("{record}: valid");
+        } else {
+            let problems = document.parse_errors.len() + document.errors.len();
+            println!("{record}: {problems} problem(s)");
+            for err in &document.parse_errors {
+                println!("  - {err}");
+            }
+            for err in &document.errors {
+                println!("  - {err}");
+            }
+        }
+    }
+}
+
 fn main() -> io::Result<()> {
+    let options = Options::from_args()?;
+    let schema = options.schema()?;
+
     let mut input = String::new();
     io::stdin().read_to_string(&mut input)?;
 
-    let passports = input
-        .split("\n\n")
-        .map(TryFrom::try_from)
+    let documents = records::records(&input)
+        .map(|(line, record)| Document::new(line, record, &schema, options.strict))
         .collect::<Vec<_>>();
 
-    part_one(&passports);
-    part_two(&passports);
+    if let Some(format) = options.export {
+        return export::export(
+            &mut io::stdout().lock(),
+            &documents,
+            format,
+            options.include_invalid,
+        );
+    }
+
+    part_one(&documents);
+    part_two(&documents);
+
+    if options.report {
+        report(&documents);
+    }
+
+    if options.stats {
+        stats::print_stats(&documents);
+    }
+
     Ok(())
 }
@@ -0,0 +1,183 @@
+use crate::height::{DistanceUnit, Height};
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::str::FromStr;
+
+/// The fields a `key:value` document may contain and the rules each field's value must pass
+#[derive(Deserialize)]
+pub struct Schema {
+    #[serde(rename = "field")]
+    fields: Vec<FieldSchema>,
+}
+
+#[derive(Deserialize)]
+pub struct FieldSchema {
+    key: String,
+    name: String,
+    #[serde(default = "required_by_default")]
+    required: bool,
+    rule: Option<Rule>,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Rule {
+    /// An integer within `min..=max`, optionally written with exactly `digits` digits
+    Integer {
+        min: i64,
+        max: i64,
+        digits: Option<usize>,
+    },
+    /// A value matched in its entirety by `pattern`
+    Regex { pattern: Pattern },
+    /// A value equal to one of `values`
+    Enum { values: Vec<String> },
+    /// An integer immediately followed by one of the units in `units`, within that unit's bounds.
+    /// When `convert` is set, a height written in a unit without bounds of its own is instead
+    /// accepted if it falls within the bounds of any distance unit it can be converted into
+    Measurement {
+        units: BTreeMap<String, Bounds>,
+        #[serde(default)]
+        convert: bool,
+    },
+}
+
+#[derive(Deserialize)]
+pub struct Bounds {
+    min: i64,
+    max: i64,
+}
+
+#[derive(Deserialize)]
+#[serde(try_from = "String")]
+pub struct Pattern(Regex);
+
+#[derive(Debug)]
+pub enum ValidationError {
+    MissingField {
+        key: String,
+        name: String,
+    },
+    /// A field that was present but whose `value` failed validation
+    InvalidField {
+        key: String,
+        name: String,
+        value: String,
+    },
+}
+
+fn required_by_default() -> bool {
+    true
+}
+
+impl FromStr for Schema {
+    type Err = toml::de::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        toml::from_str(s)
+    }
+}
+
+impl Schema {
+    pub fn has_key(&self, key: &str) -> bool {
+        self.fields.iter().any(|field| field.key == key)
+    }
+
+    /// Checks `document` against every field of this schema, returning each problem found in the
+    /// order the fields were declared
+    pub fn validate(&self, document: &HashMap<&str, &str>) -> Vec<ValidationError> {
+        self.fields
+            .iter()
+            .filter_map(|field| match document.get(field.key.as_str()) {
+                None if field.required => Some(ValidationError::missing(&field.key, &field.name)),
+                None => None,
+                Some(value) => field
+                    .rule
+                    .as_ref()
+                    .filter(|rule| !rule.accepts(value))
+                    .map(|_| ValidationError::invalid(&field.key, &field.name, value)),
+            })
+            .collect()
+    }
+}
+
+impl Rule {
+    fn accepts(&self, value: &str) -> bool {
+        match self {
+            Self::Integer { min, max, digits } => {
+                digits.is_none_or(|digits| value.len() == digits)
+                    && value.parse().is_ok_and(|n| (*min..=*max).contains(&n))
+            }
+            Self::Regex { pattern } => pattern.0.is_match(value),
+            Self::Enum { values } => values.iter().any(|v| v == value),
+            Self::Measurement { units, convert } => match value.parse::<Height>() {
+                Ok(height) => match units.get(height.1.suffix()) {
+                    Some(bounds) => bounds.contains(height.0 as f64),
+                    None => {
+                        *convert
+                            && units.iter().any(|(unit, bounds)| {
+                                unit.parse::<DistanceUnit>()
+                                    .is_ok_and(|unit| bounds.contains(height.to_unit(unit)))
+                            })
+                    }
+                },
+                Err(_) => {
+                    let split = value
+                        .find(|c: char| !c.is_ascii_digit())
+                        .unwrap_or(value.len());
+                    let (quantity, unit) = value.split_at(split);
+
+                    units.get(unit).is_some_and(|bounds| {
+                        quantity
+                            .parse()
+                            .is_ok_and(|n: i64| bounds.contains(n as f64))
+                    })
+                }
+            },
+        }
+    }
+}
+
+impl Bounds {
+    fn contains(&self, n: f64) -> bool {
+        (self.min as f64..=self.max as f64).contains(&n)
+    }
+}
+
+impl TryFrom<String> for Pattern {
+    type Error = regex::Error;
+
+    fn try_from(pattern: String) -> Result<Self, Self::Error> {
+        Regex::new(&format!("^(?:{pattern})$")).map(Self)
+    }
+}
+
+impl ValidationError {
+    pub fn missing(key: &str, name: &str) -> Self {
+        Self::MissingField {
+            key: key.to_string(),
+            name: name.to_string(),
+        }
+    }
+
+    pub fn invalid(key: &str, name: &str, value: &str) -> Self {
+        Self::InvalidField {
+            key: key.to_string(),
+            name: name.to_string(),
+            value: value.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingField { key, name } => write!(f, "missing `{key}` ({name})"),
+            Self::InvalidField { key, name, value } => {
+                write!(f, "invalid value `{value}` for `{key}` ({name})")
+            }
+        }
+    }
+}
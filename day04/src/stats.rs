@@ -0,0 +1,157 @@
+use crate::height::Height;
+use crate::schema::ValidationError;
+use crate::{Document, EyeColor, Field};
+use std::collections::BTreeMap;
+
+/// Prints `rows` beneath `headers`, padding every column to its widest cell
+fn print_table(title: &str, headers: &[&str], rows: &[Vec<String>]) {
+    let widths = headers
+        .iter()
+        .enumerate()
+        .map(|(i, header)| {
+            rows.iter()
+                .map(|row| row[i].len())
+                .chain([header.len()])
+                .max()
+                .unwrap_or_default()
+        })
+        .collect::<Vec<_>>();
+    let line = |cells: Vec<&str>| {
+        cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect::<Vec<_>>()
+            .join(" | ")
+            .trim_end()
+            .to_string()
+    };
+
+    println!("{title}");
+    println!("{}", line(headers.to_vec()));
+    println!(
+        "{}",
+        widths
+            .iter()
+            .map(|width| "-".repeat(*width))
+            .collect::<Vec<_>>()
+            .join("-+-")
+    );
+    for row in rows {
+        println!("{}", line(row.iter().map(String::as_str).collect()));
+    }
+    println!();
+}
+
+/// Counts how often each key produced by `bucket` occurs amongst the values of `field`
+fn histogram<'a, K, F>(documents: &[Document<'a>], field: Field, bucket: F) -> BTreeMap<K, usize>
+where
+    K: Ord,
+    F: Fn(&'a str) -> K,
+{
+    documents
+        .iter()
+        .filter_map(|document| document.fields.get(field.key()))
+        .fold(BTreeMap::new(), |mut histogram, value| {
+            *histogram.entry(bucket(value)).or_insert(0) += 1;
+            histogram
+        })
+}
+
+fn print_histogram<K>(title: &str, histogram: BTreeMap<K, usize>, label: impl Fn(&K) -> String) {
+    let rows = histogram
+        .iter()
+        .map(|(key, count)| vec![label(key), count.to_string()])
+        .collect::<Vec<_>>();
+
+    print_table(title, &["Value", "Passports"], &rows);
+}
+
+fn decade(year: &str) -> Option<usize> {
+    year.parse::<usize>().ok().map(|year| year / 10 * 10)
+}
+
+fn decade_label(decade: &Option<usize>) -> String {
+    decade.map_or_else(|| "unparseable".to_string(), |decade| format!("{decade}s"))
+}
+
+/// Prints a summary of how `documents` fared against their schema and how their values are
+/// distributed, where a field counts as missing even if the schema does not require it
+pub fn print_stats(documents: &[Document<'_>]) {
+    let rows = Field::ALL
+        .iter()
+        .map(|field| {
+            let missing = documents
+                .iter()
+                .filter(|document| !document.fields.contains_key(field.key()))
+                .count();
+            let invalid = documents
+                .iter()
+                .flat_map(|document| &document.errors)
+                .filter(|err| {
+                    matches!(err, ValidationError::InvalidField { key, .. } if key == field.key())
+                })
+                .count();
+
+            vec![
+                format!("{} ({})", field.key(), field.name()),
+                missing.to_string(),
+                invalid.to_string(),
+            ]
+        })
+        .collect::<Vec<_>>();
+    print_table("Field Problems", &["Field", "Missing", "Invalid"], &rows);
+
+    print_histogram(
+        "Birth Years",
+        histogram(documents, Field::BirthYear, decade),
+        decade_label,
+    );
+    print_histogram(
+        "Issue Years",
+        histogram(documents, Field::IssueYear, decade),
+        decade_label,
+    );
+    print_histogram(
+        "Expiration Years",
+        histogram(documents, Field::ExpirationYear, decade),
+        decade_label,
+    );
+    print_histogram(
+        "Eye Colors",
+        histogram(documents, Field::EyeColor, |ecl| ecl),
+        |ecl| match ecl.parse::<EyeColor>() {
+            Ok(eye_color) => format!("{ecl} ({})", eye_color.name()),
+            Err(_) => ecl.to_string(),
+        },
+    );
+    print_histogram(
+        "Heights",
+        histogram(documents, Field::Height, |hgt| {
+            hgt.parse::<Height>()
+                .ok()
+                .map(|height| height.normalized() as usize / 10 * 10)
+        }),
+        |bucket| {
+            bucket.map_or_else(
+                || "unparseable".to_string(),
+                |cm| format!("{cm}-{}cm", cm + 9),
+            )
+        },
+    );
+
+    let valid = documents.iter().filter(|d| d.is_valid()).count();
+    let valid_with_cid = documents
+        .iter()
+        .filter(|d| d.is_valid() && d.fields.contains_key(Field::CountryID.key()))
+        .count();
+    print_table(
+        "Totals",
+        &["Passports", "Valid", "Valid if `cid` were required"],
+        &[vec![
+            documents.len().to_string(),
+            valid.to_string(),
+            valid_with_cid.to_string(),
+        ]],
+    );
+}
@@ -1,7 +1,33 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt;
 use std::io::{self, Read};
+use std::ops::RangeInclusive;
 use std::str::FromStr;
 
-struct Seat(u32);
+/// The most seats `SeatMap::render` will draw before giving up
+const MAX_RENDERED_SEATS: u64 = 1 << 16;
+
+/// The shape of an aircraft's seating: how many halvings pick a row and a column, and the letters
+/// a boarding pass uses to choose the `(upper, lower)` half at each step
+#[derive(Clone, Copy)]
+struct SeatLayout {
+    row_bits: u32,
+    column_bits: u32,
+    row_letters: (char, char),
+    column_letters: (char, char),
+}
+
+struct Seat {
+    id: u32,
+    layout: SeatLayout,
+}
+
+/// How many boarding passes were seen for each occupied seat of an aircraft
+struct SeatMap {
+    layout: SeatLayout,
+    counts: BTreeMap<u32, usize>,
+}
 
 #[derive(Debug)]
 enum ParseSeatError {
@@ -9,6 +35,14 @@ enum ParseSeatError {
     InvalidLength,
 }
 
+#[derive(Debug)]
+enum SeatLayoutError {
+    /// Seat IDs must fit within a `u32`
+    TooManyBits,
+    /// Both halves of an axis were given the same letter
+    AmbiguousLetters(char),
+}
+
 fn parse_binary(s: &str, one: char, zero: char) -> Result<u32, ParseSeatError> {
     s.chars()
         .rev()
@@ -25,63 +59,410 @@ fn parse_binary(s: &str, one: char, zero: char) -> Result<u32, ParseSeatError> {
         .try_fold(0, |acc, res| res.map(|n| acc + n))
 }
 
-impl FromStr for Seat {
-    type Err = ParseSeatError;
+/// A mask of the lowest `n` bits of a `u32`
+fn low_bits(n: u32) -> u32 {
+    1u32.checked_shl(n).map_or(u32::MAX, |bit| bit - 1)
+}
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (row, column) = s.split_at(7);
-        if column.len() > 3 {
-            Err(Self::Err::InvalidLength)
+/// The inverse of `parse_binary`, writing the lowest `width` bits of `n` from most to least
+/// significant
+fn format_binary(n: u32, width: u32, one: char, zero: char) -> String {
+    (0..width)
+        .rev()
+        .map(|i| if n & (1 << i) != 0 { one } else { zero })
+        .collect()
+}
+
+impl fmt::Display for SeatLayoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TooManyBits => write!(f, "seat IDs must fit within {} bits", u32::BITS),
+            Self::AmbiguousLetters(letter) => {
+                write!(f, "`{letter}` cannot pick both halves of an axis")
+            }
+        }
+    }
+}
+
+impl Default for SeatLayout {
+    /// The layout of the puzzle's aircraft: 128 rows picked with `B`/`F` and 8 columns picked with
+    /// `R`/`L`
+    fn default() -> Self {
+        Self {
+            row_bits: 7,
+            column_bits: 3,
+            row_letters: ('B', 'F'),
+            column_letters: ('R', 'L'),
+        }
+    }
+}
+
+impl SeatLayout {
+    fn new(
+        row_bits: u32,
+        column_bits: u32,
+        row_letters: (char, char),
+        column_letters: (char, char),
+    ) -> Result<Self, SeatLayoutError> {
+        if row_bits
+            .checked_add(column_bits)
+            .is_none_or(|bits| bits > u32::BITS)
+        {
+            Err(SeatLayoutError::TooManyBits)
+        } else if row_letters.0 == row_letters.1 {
+            Err(SeatLayoutError::AmbiguousLetters(row_letters.0))
+        } else if column_letters.0 == column_letters.1 {
+            Err(SeatLayoutError::AmbiguousLetters(column_letters.0))
         } else {
-            let row = parse_binary(row, 'B', 'F')?;
-            let column = parse_binary(column, 'R', 'L')?;
+            Ok(Self {
+                row_bits,
+                column_bits,
+                row_letters,
+                column_letters,
+            })
+        }
+    }
+
+    /// Decodes a boarding pass, which must be exactly `row_bits + column_bits` characters long
+    fn decode(&self, pass: &str) -> Result<Seat, ParseSeatError> {
+        if pass.chars().count() != (self.row_bits + self.column_bits) as usize {
+            return Err(ParseSeatError::InvalidLength);
+        }
+
+        let split = pass
+            .char_indices()
+            .nth(self.row_bits as usize)
+            .map_or(pass.len(), |(i, _)| i);
+        let (row, column) = pass.split_at(split);
+        let row = parse_binary(row, self.row_letters.0, self.row_letters.1)?;
+        let column = parse_binary(column, self.column_letters.0, self.column_letters.1)?;
+
+        Ok(Seat {
+            id: row.checked_shl(self.column_bits).unwrap_or(0) | column,
+            layout: *self,
+        })
+    }
+}
+
+impl Seat {
+    fn row(&self) -> u32 {
+        self.id.checked_shr(self.layout.column_bits).unwrap_or(0)
+    }
+
+    fn column(&self) -> u32 {
+        self.id & low_bits(self.layout.column_bits)
+    }
+}
+
+impl fmt::Display for Seat {
+    /// Encodes the seat back into its boarding pass, such as `FBFBBFFRLR`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let SeatLayout {
+            row_bits,
+            column_bits,
+            row_letters: (back, front),
+            column_letters: (right, left),
+        } = self.layout;
+
+        write!(
+            f,
+            "{}{}",
+            format_binary(self.row(), row_bits, back, front),
+            format_binary(self.column(), column_bits, right, left)
+        )
+    }
+}
+
+impl SeatMap {
+    fn new(layout: SeatLayout, seats: &[Seat]) -> Self {
+        let mut counts = BTreeMap::new();
+        for seat in seats {
+            *counts.entry(seat.id).or_insert(0) += 1;
+        }
+
+        Self { layout, counts }
+    }
+
+    fn seat(&self, id: u32) -> Seat {
+        Seat {
+            id,
+            layout: self.layout,
+        }
+    }
+
+    fn columns(&self) -> u64 {
+        1 << self.layout.column_bits
+    }
+
+    fn seats(&self) -> u64 {
+        1 << (self.layout.row_bits + self.layout.column_bits)
+    }
+
+    /// Each run of unoccupied seats lying between two occupied seats
+    fn gap_ranges(&self) -> impl Iterator<Item = RangeInclusive<u32>> + '_ {
+        self.counts
+            .keys()
+            .zip(self.counts.keys().skip(1))
+            .filter(|(before, after)| *before + 1 < **after)
+            .map(|(before, after)| (before + 1)..=(after - 1))
+    }
+
+    /// Every unoccupied seat lying between the first and last occupied seats
+    fn interior_gaps(&self) -> impl Iterator<Item = u32> + '_ {
+        self.gap_ranges().flatten()
+    }
+
+    /// The number of seats, and of whole rows, before the first occupied seat
+    fn missing_front(&self) -> (u64, u64) {
+        match self.counts.keys().next() {
+            Some(&first) => (u64::from(first), u64::from(first) / self.columns()),
+            None => (self.seats(), self.seats() / self.columns()),
+        }
+    }
+
+    /// The number of seats, and of whole rows, after the last occupied seat
+    fn missing_back(&self) -> (u64, u64) {
+        match self.counts.keys().next_back() {
+            Some(&last) => {
+                let after = self.seats() - u64::from(last) - 1;
+                (after, after / self.columns())
+            }
+            None => (0, 0),
+        }
+    }
+
+    /// Every seat seen on more than one boarding pass, along with how many times it was seen
+    fn duplicates(&self) -> impl Iterator<Item = (u32, usize)> + '_ {
+        self.counts
+            .iter()
+            .filter(|(_, count)| **count > 1)
+            .map(|(id, count)| (*id, *count))
+    }
+
+    /// Draws one line per row, where `#` is an occupied seat, `!` a seat seen more than once, `o`
+    /// an interior gap and `.` a seat missing from the front or back of the aircraft
+    fn render(&self) -> Option<String> {
+        if self.seats() > MAX_RENDERED_SEATS {
+            return None;
+        }
 
-            Ok(Self((row * 8) + column))
+        let first = self.counts.keys().next().copied();
+        let last = self.counts.keys().next_back().copied();
+        let rows = self.seats() / self.columns();
+        let width = rows.saturating_sub(1).to_string().len();
+        let mut map = String::new();
+
+        for row in 0..rows {
+            map.push_str(&format!("{row:>width$} "));
+            for column in 0..self.columns() {
+                let id = (row * self.columns() + column) as u32;
+                map.push(match self.counts.get(&id) {
+                    Some(1) => '#',
+                    Some(_) => '!',
+                    None if first < Some(id) && Some(id) < last => 'o',
+                    None => '.',
+                });
+            }
+            map.push('\n');
         }
+
+        Some(map)
+    }
+}
+
+impl FromStr for Seat {
+    type Err = ParseSeatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        SeatLayout::default().decode(s)
     }
 }
 
 fn part_one(seats: &[Seat]) {
-    let answer = seats.iter().map(|s| s.0).max();
+    let answer = seats.iter().map(|s| s.id).max();
 
     println!("Part One: {answer:?}");
 }
 
-fn part_two(seats: &[Seat]) {
-    let (min, max, sum): (Option<u32>, Option<u32>, u32) =
-        seats
-            .iter()
-            .map(|s| s.0)
-            .fold((None, None, 0), |(amin, amax, asum), id| {
-                let min = amin.map(|mn| mn.min(id)).or(Some(id));
-                let max = amax.map(|mx| mx.max(id)).or(Some(id));
-                let sum = asum + id;
-                (min, max, sum)
-            });
-
-    let answer = match (min, max, sum) {
-        (Some(min), Some(max), sum) => {
-            let to_max = max * (max + 1) / 2;
-            let to_min = min * (min - 1) / 2;
-            Some(to_max - to_min - sum)
+fn part_two(seat_map: &SeatMap) {
+    let mut gaps = seat_map.interior_gaps();
+    let answer = gaps.next().filter(|_| gaps.next().is_none());
+
+    println!("Part Two: {answer:?}");
+}
+
+fn print_seat_map(seat_map: &SeatMap) {
+    match seat_map.render() {
+        Some(map) => print!("{map}"),
+        None => println!("Seat map too large to render"),
+    }
+
+    let (front_seats, front_rows) = seat_map.missing_front();
+    let (back_seats, back_rows) = seat_map.missing_back();
+    println!("Missing at front: {front_seats} seat(s), {front_rows} whole row(s)");
+    println!("Missing at back: {back_seats} seat(s), {back_rows} whole row(s)");
+
+    if let Some(&id) = seat_map.counts.keys().next_back() {
+        let seat = seat_map.seat(id);
+        println!(
+            "Highest: {seat} (ID {id}, row {}, column {})",
+            seat.row(),
+            seat.column()
+        );
+    }
+
+    for gap in seat_map.gap_ranges() {
+        let (first, last) = (seat_map.seat(*gap.start()), seat_map.seat(*gap.end()));
+        if gap.start() == gap.end() {
+            println!(
+                "Empty: {first} (ID {}, row {}, column {})",
+                first.id,
+                first.row(),
+                first.column()
+            );
+        } else {
+            println!(
+                "Empty: {first} to {last} (IDs {} to {}, {} seats)",
+                first.id,
+                last.id,
+                u64::from(last.id - first.id) + 1
+            );
+        }
+    }
+
+    for (id, count) in seat_map.duplicates() {
+        let seat = seat_map.seat(id);
+        println!("Duplicate: {seat} (ID {id}) seen {count} times");
+    }
+}
+
+/// Reads a `SeatLayout` from `[--row-bits <n>] [--column-bits <n>] [--row-letters <upper><lower>]
+/// [--column-letters <upper><lower>]`, where any omitted option keeps its default, along with
+/// whether `--map` was given
+fn options_from_args() -> io::Result<(SeatLayout, bool)> {
+    let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidInput, msg);
+    let bits = |arg: Option<String>| {
+        arg.and_then(|n| n.parse::<u32>().ok())
+            .ok_or_else(|| invalid("Expected a number of bits".to_string()))
+    };
+    let letters = |arg: Option<String>| {
+        let arg = arg.unwrap_or_default();
+        let mut chars = arg.chars();
+        match (chars.next(), chars.next(), chars.next()) {
+            (Some(upper), Some(lower), None) => Ok((upper, lower)),
+            _ => Err(invalid(format!("Expected two letters, got `{arg}`"))),
         }
-        _ => None,
     };
 
-    println!("Part Two: {answer:?}");
+    let SeatLayout {
+        mut row_bits,
+        mut column_bits,
+        mut row_letters,
+        mut column_letters,
+    } = SeatLayout::default();
+    let mut map = false;
+    let mut args = env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--map" => map = true,
+            "--row-bits" => row_bits = bits(args.next())?,
+            "--column-bits" => column_bits = bits(args.next())?,
+            "--row-letters" => row_letters = letters(args.next())?,
+            "--column-letters" => column_letters = letters(args.next())?,
+            _ => return Err(invalid(format!("Unknown argument `{arg}`"))),
+        }
+    }
+
+    let layout = SeatLayout::new(row_bits, column_bits, row_letters, column_letters)
+        .map_err(|err| invalid(format!("Invalid seat layout: {err}")))?;
+
+    Ok((layout, map))
 }
 
 fn main() -> io::Result<()> {
+    let (layout, show_map) = options_from_args()?;
+
     let mut input = String::new();
     io::stdin().read_to_string(&mut input)?;
 
     let seats = input
         .lines()
-        .map(|seat| seat.parse().expect("Invalid Boarding Pass given"))
-        .collect::<Vec<_>>();
+        .map(|pass| {
+            layout.decode(pass).map_err(|err| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Invalid boarding pass `{pass}`: {err:?}"),
+                )
+            })
+        })
+        .collect::<io::Result<Vec<_>>>()?;
+
+    let seat_map = SeatMap::new(layout, &seats);
 
     part_one(&seats);
-    part_two(&seats);
+    part_two(&seat_map);
+
+    if show_map {
+        print_seat_map(&seat_map);
+    }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let layout = SeatLayout::default();
+        for id in 0..1024 {
+            let pass = Seat { id, layout }.to_string();
+            let seat = pass.parse::<Seat>().unwrap();
+            assert_eq!(seat.id, id);
+            assert_eq!(seat.to_string(), pass);
+        }
+    }
+
+    #[test]
+    fn test_row_and_column() {
+        let seat = "FBFBBFFRLR".parse::<Seat>().unwrap();
+        assert_eq!((seat.id, seat.row(), seat.column()), (357, 44, 5));
+        let seat = "BBFFBBFRLL".parse::<Seat>().unwrap();
+        assert_eq!((seat.id, seat.row(), seat.column()), (820, 102, 4));
+    }
+
+    #[test]
+    fn test_too_many_bits() {
+        assert!(matches!(
+            SeatLayout::new(u32::MAX, 2, ('B', 'F'), ('R', 'L')),
+            Err(SeatLayoutError::TooManyBits)
+        ));
+        assert!(matches!(
+            SeatLayout::new(30, 3, ('B', 'F'), ('R', 'L')),
+            Err(SeatLayoutError::TooManyBits)
+        ));
+        assert!(SeatLayout::new(29, 3, ('B', 'F'), ('R', 'L')).is_ok());
+    }
+
+    #[test]
+    fn test_gaps() {
+        let layout = SeatLayout::default();
+        let seats = [3, 5, 6, 10].map(|id| Seat { id, layout });
+        let seat_map = SeatMap::new(layout, &seats);
+
+        assert!(seat_map.gap_ranges().eq([4..=4, 7..=9]));
+        assert!(seat_map.interior_gaps().eq([4, 7, 8, 9]));
+    }
+
+    #[test]
+    fn test_gaps_are_lazy() {
+        let layout = SeatLayout::new(29, 2, ('B', 'F'), ('R', 'L')).unwrap();
+        let seats = [0, u32::MAX >> 1].map(|id| Seat { id, layout });
+        let seat_map = SeatMap::new(layout, &seats);
+
+        assert!(seat_map.gap_ranges().eq([1..=(u32::MAX >> 1) - 1]));
+        assert!(seat_map.interior_gaps().take(2).eq([1, 2]));
+    }
+}
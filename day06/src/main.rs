@@ -1,55 +1,375 @@
-use std::collections::{HashMap, HashSet};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::env;
+use std::fmt;
 use std::io::{self, Read};
+use std::str::FromStr;
 
-fn part_one(groups: &[&str]) {
-    let answer: usize = groups
-        .iter()
-        .map(|group| {
-            group
-                .chars()
-                .filter(|c| *c != '\n')
-                .collect::<HashSet<char>>()
-                .len()
-        })
-        .sum();
-    println!("Part One: {answer}");
+/// A question about a group's answers, each of which selects the questions answered "yes" by some
+/// number of the group's people
+#[derive(Clone, Copy)]
+enum Query {
+    /// Questions anyone answered
+    Union,
+    /// Questions everyone answered
+    Intersection,
+    /// Questions at least this many people answered, where at least one person always must
+    AtLeast(usize),
+    /// Questions exactly one person answered
+    ExactlyOne,
+    /// Questions an odd number of people answered, generalizing the symmetric difference of two
+    /// sets to any number of them
+    SymmetricDifference,
 }
 
-fn part_two(groups: &[&str]) {
-    let answer: usize = groups
-        .iter()
-        .map(|group| {
-            let (people, question_frequency) = group.lines().fold(
-                (0, HashMap::new()),
-                |(people, mut question_frequency), questions| {
-                    for question in questions.chars() {
-                        question_frequency
-                            .entry(question)
-                            .and_modify(|freq| {
-                                *freq += 1;
-                            })
-                            .or_insert(1);
-                    }
-                    (people + 1, question_frequency)
-                },
-            );
+#[derive(Debug)]
+struct ParseQueryError(String);
 
-            question_frequency
-                .values()
-                .filter(|freq| **freq == people)
-                .count()
-        })
-        .sum();
+/// The answers of one group of people
+struct Group {
+    /// The 1-indexed line of the input the group starts on
+    line: usize,
+    people: usize,
+    answers: Answers,
+}
+
+enum Answers {
+    /// One mask per person, with bit `i` set if they answered question `'a' + i`
+    Bitmask(Vec<u32>),
+    /// The number of people who answered each question, used when a group answered questions
+    /// outside `'a'..='z'`
+    General(HashMap<char, usize>),
+}
+
+impl FromStr for Query {
+    type Err = ParseQueryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            None if s == "union" => Ok(Self::Union),
+            None if s == "intersection" => Ok(Self::Intersection),
+            None if s == "exactly-one" => Ok(Self::ExactlyOne),
+            None if s == "symmetric-difference" => Ok(Self::SymmetricDifference),
+            Some(("at-least", k)) => k
+                .parse()
+                .map(Self::AtLeast)
+                .map_err(|_| ParseQueryError(s.to_string())),
+            _ => Err(ParseQueryError(s.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for Query {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Union => write!(f, "union"),
+            Self::Intersection => write!(f, "intersection"),
+            Self::AtLeast(k) => write!(f, "at-least:{k}"),
+            Self::ExactlyOne => write!(f, "exactly-one"),
+            Self::SymmetricDifference => write!(f, "symmetric-difference"),
+        }
+    }
+}
+
+impl fmt::Display for ParseQueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Unknown query `{}`, expected one of `union`, `intersection`, `at-least:<k>`, \
+            `exactly-one` or `symmetric-difference`",
+            self.0
+        )
+    }
+}
+
+impl Query {
+    /// Whether a question answered by `frequency` of a group's `people` is selected
+    fn selects(&self, frequency: usize, people: usize) -> bool {
+        match self {
+            Self::Union => frequency > 0,
+            Self::Intersection => frequency == people,
+            Self::AtLeast(k) => frequency >= *k,
+            Self::ExactlyOne => frequency == 1,
+            Self::SymmetricDifference => frequency % 2 == 1,
+        }
+    }
+
+    /// The total number of questions selected across every group
+    fn evaluate(&self, groups: &[Group]) -> usize {
+        groups.iter().map(|group| group.count(*self)).sum()
+    }
+}
+
+/// Maps the questions `'a'..='z'` onto the bits of a `u32`, returning `None` for any other question
+fn question_bit(question: char) -> Option<u32> {
+    question
+        .is_ascii_lowercase()
+        .then(|| 1 << (question as u32 - 'a' as u32))
+}
+
+fn count_frequencies(frequencies: &HashMap<char, usize>, people: usize, query: Query) -> usize {
+    frequencies
+        .values()
+        .filter(|frequency| query.selects(**frequency, people))
+        .count()
+}
+
+impl Answers {
+    /// Counts how many of `people` answered each question, whatever the question is
+    fn general(people: &[&str]) -> Self {
+        Self::General(people.iter().fold(HashMap::new(), |mut freqs, questions| {
+            for question in questions.chars().collect::<HashSet<_>>() {
+                *freqs.entry(question).or_insert(0) += 1;
+            }
+            freqs
+        }))
+    }
+}
+
+impl Group {
+    /// Parses a group starting on `line` with one person's answers per line
+    fn new(line: usize, group: &str) -> Self {
+        let people = group.lines().map(str::trim).collect::<Vec<_>>();
+        let masks = people
+            .iter()
+            .map(|questions| {
+                questions
+                    .chars()
+                    .map(question_bit)
+                    .try_fold(0, |mask, bit| bit.map(|bit| mask | bit))
+            })
+            .collect::<Option<Vec<_>>>();
+
+        let answers = match masks {
+            Some(masks) => Answers::Bitmask(masks),
+            None => Answers::general(&people),
+        };
+
+        Self {
+            line,
+            people: people.len(),
+            answers,
+        }
+    }
+
+    fn count(&self, query: Query) -> usize {
+        match &self.answers {
+            Answers::Bitmask(masks) => Self::count_masks(masks, query),
+            Answers::General(frequencies) => count_frequencies(frequencies, self.people, query),
+        }
+    }
+
+    fn count_masks(masks: &[u32], query: Query) -> usize {
+        let selected = match query {
+            Query::Union => masks.iter().fold(0, |acc, mask| acc | mask),
+            Query::Intersection => masks.iter().fold(u32::MAX, |acc, mask| acc & mask),
+            Query::SymmetricDifference => masks.iter().fold(0, |acc, mask| acc ^ mask),
+            Query::ExactlyOne => {
+                // `once` holds the questions seen exactly once so far, `more` those seen again
+                let (once, _) = masks.iter().fold((0, 0), |(once, more), mask| {
+                    let more = more | (once & mask);
+                    ((once | mask) & !more, more)
+                });
+                once
+            }
+            Query::AtLeast(k) => (0..26)
+                .map(|i| 1 << i)
+                .filter(|bit| masks.iter().filter(|mask| *mask & bit != 0).count() >= k.max(1))
+                .fold(0, |acc, bit| acc | bit),
+        };
+
+        // An empty group has no questions, even though its intersection is every bit
+        if masks.is_empty() {
+            0
+        } else {
+            selected.count_ones() as usize
+        }
+    }
+
+    /// The number of people who answered each question answered by anyone in the group
+    fn frequencies(&self) -> HashMap<char, usize> {
+        match &self.answers {
+            Answers::Bitmask(masks) => Self::mask_frequencies(masks),
+            Answers::General(frequencies) => frequencies.clone(),
+        }
+    }
+
+    /// The general representation of a group of masks
+    fn mask_frequencies(masks: &[u32]) -> HashMap<char, usize> {
+        ('a'..='z')
+            .zip(0..)
+            .map(|(question, i)| {
+                let frequency = masks.iter().filter(|mask| *mask & (1 << i) != 0).count();
+                (question, frequency)
+            })
+            .filter(|(_, frequency)| *frequency > 0)
+            .collect()
+    }
+}
+
+fn part_one(groups: &[Group]) {
+    let answer = Query::Union.evaluate(groups);
+    println!("Part One: {answer}");
+}
+
+fn part_two(groups: &[Group]) {
+    let answer = Query::Intersection.evaluate(groups);
     println!("Part Two: {answer}");
 }
 
+/// Prints, for every question, how many groups had anyone and everyone answer it and the share of
+/// all people who answered it, followed by the largest and smallest groups
+fn print_stats(groups: &[Group]) {
+    let mut questions: BTreeMap<char, (usize, usize, usize)> = BTreeMap::new();
+    for group in groups {
+        for (question, frequency) in group.frequencies() {
+            let (any, all, people) = questions.entry(question).or_default();
+            *any += 1;
+            *all += usize::from(frequency == group.people);
+            *people += frequency;
+        }
+    }
+
+    let total_people: usize = groups.iter().map(|group| group.people).sum();
+    println!("Question | Any Answered | All Answered | Response Rate");
+    for (question, (any, all, people)) in questions {
+        let rate = 100.0 * people as f64 / total_people as f64;
+        println!("{question:<8} | {any:<12} | {all:<12} | {rate:.1}%");
+    }
+
+    let describe = |label: &str, group: Option<(usize, &Group)>| {
+        if let Some((i, group)) = group {
+            println!(
+                "{label} Group: #{} (line {}) with {} member(s)",
+                i + 1,
+                group.line,
+                group.people
+            );
+        }
+    };
+    describe(
+        "Largest",
+        groups
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, group)| Reverse(group.people)),
+    );
+    describe(
+        "Smallest",
+        groups
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, group)| group.people),
+    );
+}
+
 fn main() -> io::Result<()> {
+    let mut show_stats = false;
+    let queries = env::args()
+        .skip(1)
+        .filter(|arg| {
+            let stats = arg == "--stats";
+            show_stats |= stats;
+            !stats
+        })
+        .map(|arg| arg.parse::<Query>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err.to_string()))?;
+
     let mut input = String::new();
     io::stdin().read_to_string(&mut input)?;
 
-    let groups = input.split("\n\n").collect::<Vec<_>>();
+    let groups = records::records(&input)
+        .map(|(line, group)| Group::new(line, group))
+        .collect::<Vec<_>>();
+
+    if queries.is_empty() && !show_stats {
+        part_one(&groups);
+        part_two(&groups);
+    }
+
+    for query in queries {
+        println!("{query}: {}", query.evaluate(&groups));
+    }
+
+    if show_stats {
+        print_stats(&groups);
+    }
 
-    part_one(&groups);
-    part_two(&groups);
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const QUERIES: [Query; 8] = [
+        Query::Union,
+        Query::Intersection,
+        Query::AtLeast(0),
+        Query::AtLeast(1),
+        Query::AtLeast(2),
+        Query::AtLeast(3),
+        Query::ExactlyOne,
+        Query::SymmetricDifference,
+    ];
+
+    /// The group parsed from `group` as usual, alongside the same group forced into the general
+    /// representation
+    fn both_representations(group: &str) -> (Group, Group) {
+        let people = group.lines().map(str::trim).collect::<Vec<_>>();
+        let general = Group {
+            line: 1,
+            people: people.len(),
+            answers: Answers::general(&people),
+        };
+
+        (Group::new(1, group), general)
+    }
+
+    #[test]
+    fn test_bitmask_matches_general() {
+        let groups = [
+            "abc",
+            "a\nb\nc",
+            "ab\nac",
+            "a\na\na\na",
+            "b",
+            "abcxyz\nxyz\nzyx\nbz",
+            "a\n\nab",
+            "\n",
+            "",
+            "  ab\n\tac ",
+        ];
+
+        for group in groups {
+            let (bitmask, general) = both_representations(group);
+            assert!(matches!(bitmask.answers, Answers::Bitmask(_)), "{group:?}");
+            assert_eq!(bitmask.frequencies(), general.frequencies(), "{group:?}");
+            for query in QUERIES {
+                assert_eq!(
+                    bitmask.count(query),
+                    general.count(query),
+                    "`{query}` disagrees for {group:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_general_outside_a_to_z() {
+        let (group, _) = both_representations("aB1\na?\nab");
+        assert!(matches!(group.answers, Answers::General(_)));
+
+        let expected = [
+            (Query::Union, 5),
+            (Query::Intersection, 1),
+            (Query::AtLeast(2), 1),
+            (Query::ExactlyOne, 4),
+            (Query::SymmetricDifference, 5),
+        ];
+        for (query, count) in expected {
+            assert_eq!(group.count(query), count, "`{query}`");
+        }
+    }
+}
@@ -0,0 +1,77 @@
+//! Summarises the shape of a set of bag rules
+
+use crate::graph::BagGraph;
+use std::collections::HashMap;
+
+fn print_colors(label: &str, colors: &[&str]) {
+    println!("{label} ({}): {}", colors.len(), colors.join(", "));
+}
+
+/// Prints the colours nothing contains, the colours containing nothing, the longest chain of bags
+/// inside bags, the colour containing the most bags and every colour in topological order, for
+/// rules that have already passed `BagGraph::validate`
+pub fn print_analysis(bags: &BagGraph<'_>) {
+    let order = bags
+        .topological_order()
+        .expect("Validated rules contain no cycles");
+
+    println!(
+        "Colours: {} ({} defined)",
+        order.len(),
+        bags.defined().len()
+    );
+    let roots = bags
+        .colors()
+        .into_iter()
+        .filter(|color| bags.containers(color).unwrap_or_default().is_empty())
+        .collect::<Vec<_>>();
+    print_colors("Roots", &roots);
+    let leaves = bags
+        .colors()
+        .into_iter()
+        .filter(|color| bags.contents(color).unwrap_or_default().is_empty())
+        .collect::<Vec<_>>();
+    print_colors("Leaves", &leaves);
+
+    // The most bags nested inside one another starting from each colour, along with the colour it
+    // contains that continues the chain, filled in from the innermost colours outwards
+    let mut depths: HashMap<&str, (usize, Option<&str>)> = HashMap::new();
+    for color in order.iter().rev() {
+        let deepest = bags
+            .contents(color)
+            .unwrap_or_default()
+            .iter()
+            .map(|child| (depths[child.bag].0 + 1, Some(child.bag)))
+            .max_by_key(|(depth, _)| *depth)
+            .unwrap_or((0, None));
+        depths.insert(color, deepest);
+    }
+    if let Some(start) = order.iter().copied().max_by_key(|color| depths[color].0) {
+        let mut chain = vec![start];
+        while let (_, Some(next)) = depths[chain.last().unwrap()] {
+            chain.push(next);
+        }
+        println!(
+            "Maximum Depth: {} ({})",
+            depths[start].0,
+            chain.join(" -> ")
+        );
+    }
+
+    let most = bags.total_contents_all().map(|totals| {
+        totals
+            .into_iter()
+            .map(|(color, total)| (total, color))
+            .max()
+    });
+    match most {
+        Ok(Some((total, color))) => println!("Most Bags: {color} with {total} bag(s) inside"),
+        Ok(None) => {}
+        Err(err) => println!("Most Bags: {err}"),
+    }
+
+    println!("Topological Order:");
+    for (i, color) in order.iter().enumerate() {
+        println!("{:>4}. {color}", i + 1);
+    }
+}
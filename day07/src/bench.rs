@@ -0,0 +1,90 @@
+//! Counts bags on generated rule sets shaped like a chain of diamonds, where naively expanding
+//! every path would take time exponential in the depth of the chain
+
+use crate::graph::CountError;
+use crate::parse_rules;
+use std::time::Instant;
+
+/// A colour name for `side` bags at `level` of the chain, spelling the level out in letters since
+/// colours cannot contain digits, behind a prefix so the name is never `bag` or `bags`
+fn color(level: usize, side: &str) -> String {
+    let mut name = String::new();
+    let mut level = level;
+    loop {
+        name.insert(0, (b'a' + (level % 26) as u8) as char);
+        level /= 26;
+        if level == 0 {
+            break;
+        }
+    }
+    format!("tier{name} {side}")
+}
+
+/// Rules for `depth` levels of left and right bags, each containing `quantity` of both bags of the
+/// next level, so the number of paths from the top doubles with every level
+fn diamond_rules(depth: usize, quantity: u32) -> String {
+    let plural = if quantity == 1 { "" } else { "s" };
+    let mut rules = String::new();
+    for level in 0..depth {
+        for side in ["left", "right"] {
+            rules.push_str(&format!(
+                "{} bags contain {quantity} {} bag{plural}, {quantity} {} bag{plural}.\n",
+                color(level, side),
+                color(level + 1, "left"),
+                color(level + 1, "right"),
+            ));
+        }
+    }
+    for side in ["left", "right"] {
+        rules.push_str(&format!(
+            "{} bags contain no other bags.\n",
+            color(depth, side)
+        ));
+    }
+    rules
+}
+
+#[test]
+fn test_diamond_totals() {
+    let rules = diamond_rules(20, 1);
+    let bags = parse_rules(&rules);
+    assert_eq!(
+        bags.total_contents(&color(0, "left")).unwrap(),
+        (1 << 21) - 2
+    );
+    assert_eq!(
+        bags.total_contents_all().unwrap()[color(0, "right").as_str()],
+        (1 << 21) - 2
+    );
+
+    let rules = diamond_rules(40, 2);
+    let bags = parse_rules(&rules);
+    assert!(matches!(
+        bags.total_contents(&color(0, "left")),
+        Err(CountError::Overflow(_))
+    ));
+    assert!(matches!(
+        bags.total_contents_all(),
+        Err(CountError::Overflow(_))
+    ));
+}
+
+/// Times counting the bags inside the top of diamond chains of increasing depth, run with
+/// `cargo test --release -- --ignored --nocapture`
+#[test]
+#[ignore]
+fn bench_diamonds() {
+    // Every total still fits in a `u64` at these depths, while expanding every path would take
+    // up to 2^62 steps
+    for depth in [16, 32, 48, 62] {
+        let rules = diamond_rules(depth, 1);
+
+        let start = Instant::now();
+        let bags = parse_rules(&rules);
+        let parsed = start.elapsed();
+        let total = bags.total_contents(&color(0, "left")).unwrap();
+        let counted = start.elapsed() - parsed;
+
+        println!("Depth {depth}: {total} bag(s) (parsed in {parsed:?}, counted in {counted:?})");
+    }
+}
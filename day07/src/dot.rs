@@ -0,0 +1,55 @@
+//! Writes bag rules as a Graphviz DOT graph, with an edge from each colour to every colour it
+//! directly contains labelled with how many it holds
+
+use crate::graph::{BagGraph, UnknownColorError};
+use std::collections::BTreeSet;
+use std::io::{self, Write};
+
+/// Which colours to include in a DOT graph
+pub enum Subgraph {
+    All,
+    /// The colour and every colour it can eventually contain
+    From(String),
+    /// The colour and every colour that can eventually contain it
+    To(String),
+}
+
+impl Subgraph {
+    fn colors<'a>(&self, bags: &BagGraph<'a>) -> Result<BTreeSet<&'a str>, UnknownColorError> {
+        let (color, reachable) = match self {
+            Self::All => return Ok(bags.colors()),
+            Self::From(color) => (color, bags.all_contents(color)?),
+            Self::To(color) => (color, bags.all_containers(color)?),
+        };
+
+        Ok(bags
+            .colors()
+            .into_iter()
+            .filter(|known| known == color || reachable.contains(known))
+            .collect())
+    }
+}
+
+/// Writes the colours of `subgraph` to `out`, along with every rule between two of them
+pub fn export<W: Write>(out: &mut W, bags: &BagGraph<'_>, subgraph: &Subgraph) -> io::Result<()> {
+    let colors = subgraph
+        .colors(bags)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err.to_string()))?;
+
+    writeln!(out, "digraph bags {{")?;
+    for color in &colors {
+        writeln!(out, "    \"{color}\";")?;
+    }
+    for parent in &colors {
+        for child in bags.contents(parent).unwrap_or_default() {
+            if colors.contains(child.bag) {
+                writeln!(
+                    out,
+                    "    \"{parent}\" -> \"{}\" [label=\"{}\"];",
+                    child.bag, child.quantity
+                )?;
+            }
+        }
+    }
+    writeln!(out, "}}")
+}
@@ -0,0 +1,438 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt;
+
+/// An edge of a `BagGraph`: `quantity` bags of colour `bag`
+#[derive(Clone, Debug, PartialEq)]
+pub struct BagRule<'a> {
+    pub quantity: u32,
+    pub bag: &'a str,
+}
+
+/// Every bag colour along with which colours each directly contains and is directly contained by
+#[derive(Default)]
+pub struct BagGraph<'a> {
+    /// Every colour with a rule of its own, in the order the rules were given
+    defined: Vec<&'a str>,
+    /// The bags each colour directly contains
+    contents: HashMap<&'a str, Vec<BagRule<'a>>>,
+    /// The bags each colour is directly contained by, where `quantity` is how many of the colour
+    /// the containing bag holds
+    containers: HashMap<&'a str, Vec<BagRule<'a>>>,
+}
+
+/// A colour that is neither defined nor contained by any rule, along with the known colours whose
+/// names are closest to it
+#[derive(Debug)]
+pub struct UnknownColorError {
+    pub color: String,
+    pub similar: Vec<String>,
+}
+
+impl fmt::Display for UnknownColorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Could not find {} bag", self.color)?;
+        if !self.similar.is_empty() {
+            write!(f, ", did you mean {}?", self.similar.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// A path of colours, each containing the next, that starts and ends with the same colour
+#[derive(Debug, PartialEq)]
+pub struct Cycle<'a>(pub Vec<&'a str>);
+
+impl fmt::Display for Cycle<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Bags contain themselves: {}", self.0.join(" -> "))
+    }
+}
+
+/// Why the contents of a bag could not be counted
+#[derive(Debug)]
+pub enum CountError<'a> {
+    UnknownColor(UnknownColorError),
+    Cycle(Cycle<'a>),
+    /// A colour whose total contents do not fit in a `u64`
+    Overflow(&'a str),
+}
+
+impl fmt::Display for CountError<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownColor(err) => err.fmt(f),
+            Self::Cycle(cycle) => cycle.fmt(f),
+            Self::Overflow(color) => write!(f, "Too many bags inside {color} bags to count"),
+        }
+    }
+}
+
+/// A problem with a rule set that stops its bags from being counted
+#[derive(Debug)]
+pub enum RuleError<'a> {
+    Cycle(Cycle<'a>),
+    /// A colour contained by other colours that has no rule of its own
+    Undefined {
+        color: &'a str,
+        contained_by: Vec<&'a str>,
+    },
+}
+
+impl fmt::Display for RuleError<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Cycle(cycle) => cycle.fmt(f),
+            Self::Undefined {
+                color,
+                contained_by,
+            } => write!(
+                f,
+                "No rule defines {color} bags, which are contained by {}",
+                contained_by.join(", ")
+            ),
+        }
+    }
+}
+
+/// The number of single character insertions, deletions and substitutions needed to turn `a` into
+/// `b`
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+/// Adds `quantity` bags that each contain `inner` bags to `total`, or `None` on overflow
+fn add_contents(total: u64, quantity: u32, inner: u64) -> Option<u64> {
+    inner
+        .checked_add(1)?
+        .checked_mul(quantity.into())?
+        .checked_add(total)
+}
+
+impl<'a> BagGraph<'a> {
+    /// Whether `color` has a rule of its own or appears within another colour's rule
+    pub fn has_color(&self, color: &str) -> bool {
+        self.contents.contains_key(color) || self.containers.contains_key(color)
+    }
+
+    /// Every colour with a rule of its own, in the order the rules were given
+    pub fn defined(&self) -> &[&'a str] {
+        &self.defined
+    }
+
+    /// Every colour, whether or not it has a rule of its own
+    pub fn colors(&self) -> BTreeSet<&'a str> {
+        self.contents
+            .keys()
+            .chain(self.containers.keys())
+            .copied()
+            .collect()
+    }
+
+    /// This graph's own copy of `color`, suggesting up to three colours with similar names if it is
+    /// unknown
+    fn find_color(&self, color: &str) -> Result<&'a str, UnknownColorError> {
+        if let Some((known, _)) = self
+            .contents
+            .get_key_value(color)
+            .or_else(|| self.containers.get_key_value(color))
+        {
+            return Ok(known);
+        }
+
+        let mut similar = self
+            .colors()
+            .into_iter()
+            .map(|known| (edit_distance(color, known), known))
+            .filter(|(distance, _)| *distance <= color.len().max(4) / 2)
+            .collect::<Vec<_>>();
+        similar.sort();
+
+        Err(UnknownColorError {
+            color: color.to_string(),
+            similar: similar
+                .into_iter()
+                .take(3)
+                .map(|(_, known)| known.to_string())
+                .collect(),
+        })
+    }
+
+    /// The bags `color` directly contains, or `None` if the colour is unknown
+    pub fn contents(&self, color: &str) -> Option<&[BagRule<'a>]> {
+        self.has_color(color).then(|| {
+            self.contents
+                .get(color)
+                .map(Vec::as_slice)
+                .unwrap_or_default()
+        })
+    }
+
+    /// The bags directly containing `color`, or `None` if the colour is unknown
+    pub fn containers(&self, color: &str) -> Option<&[BagRule<'a>]> {
+        self.has_color(color).then(|| {
+            self.containers
+                .get(color)
+                .map(Vec::as_slice)
+                .unwrap_or_default()
+        })
+    }
+
+    /// Every colour that can eventually contain a `color` bag
+    pub fn all_containers(&self, color: &str) -> Result<HashSet<&'a str>, UnknownColorError> {
+        Ok(Self::explore(self.find_color(color)?, &self.containers))
+    }
+
+    /// Every colour a `color` bag can eventually contain
+    pub fn all_contents(&self, color: &str) -> Result<HashSet<&'a str>, UnknownColorError> {
+        Ok(Self::explore(self.find_color(color)?, &self.contents))
+    }
+
+    /// Every colour reachable from `color` by repeatedly following `edges`, excluding `color`
+    /// itself unless it can reach itself
+    fn explore(color: &'a str, edges: &HashMap<&'a str, Vec<BagRule<'a>>>) -> HashSet<&'a str> {
+        let mut explored = HashSet::new();
+        let mut unexplored = vec![color];
+        while let Some(bag) = unexplored.pop() {
+            for next in edges.get(bag).into_iter().flatten() {
+                if explored.insert(next.bag) {
+                    unexplored.push(next.bag);
+                }
+            }
+        }
+
+        explored
+    }
+
+    /// The total number of bags a single `color` bag contains, counting the contents of each colour
+    /// only once however many paths lead to it
+    pub fn total_contents(&self, color: &str) -> Result<u64, CountError<'a>> {
+        let color = self.find_color(color).map_err(CountError::UnknownColor)?;
+        let mut inside = Self::explore(color, &self.contents);
+        inside.insert(color);
+
+        let totals = self.fill_totals(|bag| inside.contains(bag))?;
+        Ok(totals[color])
+    }
+
+    /// The total number of bags a single bag of every colour contains
+    pub fn total_contents_all(&self) -> Result<HashMap<&'a str, u64>, CountError<'a>> {
+        self.fill_totals(|_| true)
+    }
+
+    /// The total contents of every colour matching `filter`, filled in once from the innermost
+    /// colours outwards, where `filter` must match everything inside any colour it matches
+    fn fill_totals(
+        &self,
+        filter: impl Fn(&str) -> bool,
+    ) -> Result<HashMap<&'a str, u64>, CountError<'a>> {
+        let order = self.topological_order().ok_or_else(|| {
+            CountError::Cycle(
+                self.find_cycle()
+                    .expect("Only cycles prevent a topological order"),
+            )
+        })?;
+
+        let mut totals = HashMap::new();
+        for color in order.into_iter().rev().filter(|color| filter(color)) {
+            let total =
+                self.contents(color)
+                    .unwrap_or_default()
+                    .iter()
+                    .try_fold(0u64, |total, br| {
+                        add_contents(total, br.quantity, totals[br.bag])
+                            .ok_or(CountError::Overflow(color))
+                    })?;
+            totals.insert(color, total);
+        }
+
+        Ok(totals)
+    }
+
+    /// Every colour ordered so that each comes before every colour it contains, breaking ties
+    /// alphabetically, or `None` if the rules contain a cycle
+    pub fn topological_order(&self) -> Option<Vec<&'a str>> {
+        let mut containers = self
+            .colors()
+            .into_iter()
+            .map(|color| (color, self.containers(color).unwrap_or_default().len()))
+            .collect::<HashMap<_, _>>();
+        let mut ready = containers
+            .iter()
+            .filter(|(_, count)| **count == 0)
+            .map(|(color, _)| *color)
+            .collect::<BTreeSet<_>>();
+
+        let mut order = Vec::with_capacity(containers.len());
+        while let Some(color) = ready.pop_first() {
+            order.push(color);
+            for child in self.contents(color).unwrap_or_default() {
+                let count = containers.get_mut(child.bag).unwrap();
+                *count -= 1;
+                if *count == 0 {
+                    ready.insert(child.bag);
+                }
+            }
+        }
+
+        (order.len() == containers.len()).then_some(order)
+    }
+
+    /// Finds every colour that is contained by others but never defined, and the first cycle
+    /// amongst the rules if there is one
+    pub fn validate(&self) -> Result<(), Vec<RuleError<'a>>> {
+        let mut errors = self
+            .colors()
+            .into_iter()
+            .filter(|color| !self.contents.contains_key(color))
+            .map(|color| RuleError::Undefined {
+                color,
+                contained_by: self
+                    .containers(color)
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|br| br.bag)
+                    .collect(),
+            })
+            .collect::<Vec<_>>();
+        errors.extend(self.find_cycle().map(RuleError::Cycle));
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// A path of colours, each directly containing the next, from some colour back to itself
+    fn find_cycle(&self) -> Option<Cycle<'a>> {
+        // Colours on the current path are `false`, and those with every descendant explored `true`
+        let mut explored = HashMap::new();
+
+        for root in self.colors() {
+            if explored.contains_key(root) {
+                continue;
+            }
+
+            // Each colour on the path alongside the index of the next of its contents to explore
+            let mut path = vec![(root, 0)];
+            explored.insert(root, false);
+            while let Some(&(color, next)) = path.last() {
+                let Some(child) = self.contents(color).unwrap_or_default().get(next) else {
+                    explored.insert(color, true);
+                    path.pop();
+                    continue;
+                };
+
+                path.last_mut().unwrap().1 += 1;
+                match explored.get(child.bag) {
+                    Some(false) => {
+                        let start = path.iter().position(|(c, _)| *c == child.bag).unwrap();
+                        let mut cycle = path[start..].iter().map(|(c, _)| *c).collect::<Vec<_>>();
+                        cycle.push(child.bag);
+                        return Some(Cycle(cycle));
+                    }
+                    Some(true) => {}
+                    None => {
+                        explored.insert(child.bag, false);
+                        path.push((child.bag, 0));
+                    }
+                }
+            }
+        }
+
+        None
+    }
+}
+
+impl<'a> FromIterator<(&'a str, Vec<BagRule<'a>>)> for BagGraph<'a> {
+    fn from_iter<T: IntoIterator<Item = (&'a str, Vec<BagRule<'a>>)>>(iter: T) -> Self {
+        let mut graph = Self::default();
+
+        for (parent, children) in iter {
+            if !graph.contents.contains_key(parent) {
+                graph.defined.push(parent);
+            }
+            for child in &children {
+                graph
+                    .containers
+                    .entry(child.bag)
+                    .or_default()
+                    .push(BagRule {
+                        quantity: child.quantity,
+                        bag: parent,
+                    });
+            }
+            graph.contents.entry(parent).or_default().extend(children);
+        }
+
+        graph
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parse_rules;
+
+    #[test]
+    fn test_self_loop() {
+        let bags = parse_rules("a b bags contain 1 a b bag.");
+        assert!(matches!(
+            bags.validate().unwrap_err().as_slice(),
+            [RuleError::Cycle(cycle)] if *cycle == Cycle(vec!["a b", "a b"])
+        ));
+        assert!(bags.topological_order().is_none());
+    }
+
+    #[test]
+    fn test_two_cycle() {
+        let bags = parse_rules(
+            "light red bags contain 2 dark blue bags.\n\
+            dark blue bags contain 1 light red bag, 3 faded green bags.\n\
+            faded green bags contain no other bags.",
+        );
+        assert!(matches!(
+            bags.validate().unwrap_err().as_slice(),
+            [RuleError::Cycle(cycle)] if *cycle == Cycle(vec!["dark blue", "light red", "dark blue"])
+        ));
+        assert!(matches!(
+            bags.total_contents_all(),
+            Err(CountError::Cycle(_))
+        ));
+    }
+
+    #[test]
+    fn test_undefined() {
+        let bags = parse_rules(
+            "light red bags contain 2 dark blue bags, 1 faded green bag.\n\
+            muted yellow bags contain 3 dark blue bags.\n\
+            faded green bags contain no other bags.",
+        );
+        assert!(matches!(
+            bags.validate().unwrap_err().as_slice(),
+            [RuleError::Undefined { color: "dark blue", contained_by }]
+                if *contained_by == ["light red", "muted yellow"]
+        ));
+    }
+
+    #[test]
+    fn test_valid() {
+        let bags = parse_rules(
+            "light red bags contain 2 dark blue bags.\n\
+            dark blue bags contain no other bags.",
+        );
+        assert!(bags.validate().is_ok());
+    }
+}
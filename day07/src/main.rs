@@ -1,183 +1,376 @@
+mod analysis;
+#[cfg(test)]
+mod bench;
+mod dot;
+mod graph;
+mod pretty;
+
 use std::{
-    collections::{HashMap, HashSet},
+    env, fmt,
     io::{self, Read},
-    ops::{Deref, DerefMut},
 };
 
-use pest::{iterators::Pairs, Parser};
+use dot::Subgraph;
+use graph::{BagGraph, BagRule, CountError, UnknownColorError};
+use pest::{
+    error::{Error, ErrorVariant, LineColLocation},
+    iterators::Pair,
+    Parser,
+};
 use pest_derive::Parser;
+use pretty::{Order, ParseOrderError};
 
 #[derive(Parser)]
 #[grammar = "grammar.pest"]
 struct RuleParser;
 
-#[derive(Clone)]
-struct BagRule<'a> {
-    quantity: u32,
-    bag: &'a str,
+/// A line of input that does not match `grammar.pest`
+#[derive(Debug)]
+struct ParseRuleError<'a> {
+    /// The 1-indexed line of the input
+    line: usize,
+    text: &'a str,
+    error: Box<Error<Rule>>,
 }
 
-#[derive(Default)]
-struct Bags<'a>(HashMap<&'a str, Vec<BagRule<'a>>>);
+impl fmt::Display for ParseRuleError<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let column = match self.error.line_col {
+            LineColLocation::Pos((_, column)) | LineColLocation::Span((_, column), _) => column,
+        };
+        let rest = self.text.chars().skip(column - 1).collect::<String>();
+        let found = rest.split_whitespace().next().unwrap_or_default();
 
-impl<'a> BagRule<'a> {
-    fn scaled_by(&self, factor: u32) -> Self {
-        Self {
-            quantity: self.quantity * factor,
-            bag: self.bag,
+        write!(f, "line {}, column {column}: ", self.line)?;
+        match &self.error.variant {
+            ErrorVariant::ParsingError { positives, .. } => {
+                let expected = positives.iter().map(describe).collect::<Vec<_>>();
+                write!(f, "expected {}", expected.join(" or "))?;
+                if found.is_empty() {
+                    write!(f, ", found the end of the line")
+                } else {
+                    write!(f, ", found `{found}`")
+                }
+            }
+            ErrorVariant::CustomError { message } => write!(f, "{message}"),
         }
     }
 }
 
-impl<'a> Deref for Bags<'a> {
-    type Target = HashMap<&'a str, Vec<BagRule<'a>>>;
-
-    fn deref(&self) -> &Self::Target {
-        &self.0
+/// How a rule of `grammar.pest` is described when the grammar expected it
+fn describe(rule: &Rule) -> &'static str {
+    match rule {
+        Rule::EOI => "the end of the line",
+        Rule::color | Rule::word => "a colour such as `shiny gold`",
+        Rule::keyword => "`bag` or `bags`",
+        Rule::bag | Rule::bags => "a colour followed by `bag` or `bags`",
+        Rule::quantity => "a quantity",
+        Rule::empty_rule => "`no other bags`",
+        Rule::non_empty_rule | Rule::singular_rule | Rule::plural_rule => {
+            "a quantity of bags such as `2 shiny gold bags`"
+        }
+        Rule::separator => "`, `",
+        Rule::terminator => "`.`",
+        Rule::contain => "`contain`",
+        Rule::rule_set => "`no other bags` or a list of bags",
+        Rule::definition => "a rule such as `shiny gold bags contain no other bags.`",
     }
 }
 
-impl<'a> DerefMut for Bags<'a> {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.0
-    }
+/// Extracts the colour a definition is for and the bags it directly contains
+///
+/// The grammar accepts keywords in any case, but colours borrow from `line` exactly as written, so
+/// callers wanting `Shiny Gold` and `shiny gold` to be the same colour must lowercase the rules
+/// first, as `main` does
+pub fn parse_definition(line: &str) -> Result<(&str, Vec<BagRule<'_>>), Box<Error<Rule>>> {
+    let mut definition = RuleParser::parse(Rule::definition, line)?
+        .next()
+        .unwrap()
+        .into_inner();
+    let parent = inner_str(definition.next().unwrap());
+    let children = definition
+        .find(|pair| pair.as_rule() == Rule::rule_set)
+        .unwrap()
+        .into_inner()
+        .filter_map(|child_pair| match child_pair.as_rule() {
+            Rule::non_empty_rule => {
+                let mut child_rule = child_pair.into_inner();
+                let quantity = child_rule.next().unwrap();
+                let bag = inner_str(child_rule.next().unwrap());
+                Some(match quantity.as_str().parse() {
+                    Ok(quantity) => Ok(BagRule { quantity, bag }),
+                    Err(_) => Err(Box::new(Error::new_from_span(
+                        ErrorVariant::CustomError {
+                            message: format!("quantity {} is too large", quantity.as_str()),
+                        },
+                        quantity.as_span(),
+                    ))),
+                })
+            }
+            Rule::empty_rule | Rule::separator | Rule::terminator => None,
+            _ => unreachable!(),
+        })
+        .collect::<Result<_, _>>()?;
+
+    Ok((parent, children))
 }
 
-impl<'a> FromIterator<(&'a str, BagRule<'a>)> for Bags<'a> {
-    fn from_iter<T: IntoIterator<Item = (&'a str, BagRule<'a>)>>(iter: T) -> Self {
-        let mut bags = Self::default();
+/// Parses one rule per line of `rules`, which must all be valid
+#[cfg(test)]
+fn parse_rules(rules: &str) -> BagGraph<'_> {
+    rules
+        .lines()
+        .map(parse_definition)
+        .collect::<Result<_, _>>()
+        .expect("Test rules should parse")
+}
 
-        for (parent, child) in iter {
-            let children = bags.entry(parent).or_insert_with(Default::default);
-            children.push(child);
-        }
+/// The text of the first pair nested within `pair`, such as the colour of a `bag`
+fn inner_str(pair: Pair<'_, Rule>) -> &str {
+    pair.into_inner().next().unwrap().as_str()
+}
 
-        bags
-    }
+/// The colour both parts ask about when no others are given
+const DEFAULT_COLOR: &str = "shiny gold";
+
+fn unknown_color(err: UnknownColorError) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, err.to_string())
 }
 
-fn part_one<'a, I>(rules: I)
-where
-    I: Iterator<Item = (&'a str, Pairs<'a, Rule>)>,
-{
-    let bags: Bags<'a> = rules
-        .flat_map(|(parent, children)| {
-            children.filter_map(|child_pair| match child_pair.as_rule() {
-                Rule::non_empty_rule => {
-                    let mut child_rule = child_pair.into_inner();
-                    let quantity = child_rule.next().unwrap().as_str().parse().unwrap();
-                    let child = child_rule
-                        .next()
-                        .unwrap()
-                        .into_inner()
-                        .next()
-                        .unwrap()
-                        .as_str();
-                    Some((
-                        child,
-                        BagRule {
-                            quantity,
-                            bag: parent,
-                        },
-                    ))
-                }
-                Rule::empty_rule => None,
-                _ => unreachable!(),
-            })
-        })
-        .collect();
-
-    let mut explored = HashSet::new();
-    let mut unexplored = bags
-        .get("shiny gold")
-        .expect("Could not find shiny gold bag")
-        .iter()
-        .map(|br| br.bag)
-        .collect::<Vec<_>>();
-
-    while let Some(child) = unexplored.pop() {
-        explored.insert(child);
-        if let Some(parents) = bags.get(child) {
-            unexplored.extend(
-                parents
-                    .iter()
-                    .filter_map(|p| (!explored.contains(p.bag)).then(|| p.bag)),
-            );
-        }
-    }
+fn part_one(bags: &BagGraph<'_>) -> io::Result<()> {
+    let answer = bags.all_containers(DEFAULT_COLOR).map_err(unknown_color)?;
+    println!("Part One: {}", answer.len());
+    Ok(())
+}
 
-    println!("Part One: {}", explored.len());
+fn count_failed(err: CountError<'_>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err.to_string())
 }
 
-fn part_two<'a, I>(rules: I)
-where
-    I: Iterator<Item = (&'a str, Pairs<'a, Rule>)>,
-{
-    let bags: Bags<'a> = rules
-        .flat_map(|(parent, children)| {
-            children.filter_map(move |child_pair| match child_pair.as_rule() {
-                Rule::non_empty_rule => {
-                    let mut child_rule = child_pair.into_inner();
-                    let quantity = child_rule.next().unwrap().as_str().parse().unwrap();
-                    let child = child_rule
-                        .next()
-                        .unwrap()
-                        .into_inner()
-                        .next()
-                        .unwrap()
-                        .as_str();
-                    Some((
-                        parent,
-                        BagRule {
-                            quantity,
-                            bag: child,
-                        },
+fn part_two(bags: &BagGraph<'_>) -> io::Result<()> {
+    let answer = bags.total_contents(DEFAULT_COLOR).map_err(count_failed)?;
+    println!("Part Two: {answer}");
+    Ok(())
+}
+
+/// `[--analyze] [--dot | --dot-from <colour> | --dot-to <colour>]
+/// [--print <original|sorted>] [<colour>...]`
+#[derive(Default)]
+struct Options {
+    /// Colours to report on instead of the puzzle answers
+    colors: Vec<String>,
+    /// When set, the rules are written to stdout as a DOT graph instead of the puzzle answers
+    dot: Option<Subgraph>,
+    /// Whether to summarise the shape of the rules instead of giving the puzzle answers
+    analyze: bool,
+    /// When set, the rules are written back to stdout in this order instead of the puzzle answers
+    print: Option<Order>,
+}
+
+impl Options {
+    fn from_args() -> io::Result<Self> {
+        let mut options = Self::default();
+        let mut args = env::args().skip(1);
+        let missing = |flag: &str, value: &str| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Expected a {value} after {flag}"),
+            )
+        };
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--analyze" => options.analyze = true,
+                "--dot" => options.dot = Some(Subgraph::All),
+                "--dot-from" => {
+                    let color = args.next().ok_or_else(|| missing(&arg, "colour"))?;
+                    options.dot = Some(Subgraph::From(color.to_lowercase()));
+                }
+                "--dot-to" => {
+                    let color = args.next().ok_or_else(|| missing(&arg, "colour"))?;
+                    options.dot = Some(Subgraph::To(color.to_lowercase()));
+                }
+                "--print" => {
+                    let order = args.next().unwrap_or_default();
+                    options.print = Some(order.parse().map_err(|err: ParseOrderError| {
+                        io::Error::new(io::ErrorKind::InvalidInput, err.to_string())
+                    })?);
+                }
+                _ if arg.starts_with("--") => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!("Unknown argument `{arg}`"),
                     ))
                 }
-                Rule::empty_rule => None,
-                _ => unreachable!(),
-            })
-        })
-        .collect();
-
-    let mut total = 0;
-    let mut bag_stack = bags
-        .get("shiny gold")
-        .cloned()
-        .expect("Could not find shiny gold bag");
-
-    while let Some(rule) = bag_stack.pop() {
-        total += rule.quantity;
-        if let Some(children) = bags.get(rule.bag) {
-            bag_stack.extend(children.iter().map(|br| br.scaled_by(rule.quantity)))
+                _ => options.colors.push(arg.to_lowercase()),
+            }
         }
-    }
 
-    println!("Part Two: {total}");
+        Ok(options)
+    }
 }
 
 fn main() -> io::Result<()> {
+    let options = Options::from_args()?;
+
     let mut input = String::new();
     io::stdin().read_to_string(&mut input)?;
+    // The grammar accepts any case, but colours only match when written in the same case
+    let input = input.to_lowercase();
+
+    let mut errors = Vec::new();
+    let bags = input
+        .lines()
+        .enumerate()
+        .filter_map(|(i, text)| {
+            parse_definition(text)
+                .map_err(|error| {
+                    errors.push(ParseRuleError {
+                        line: i + 1,
+                        text,
+                        error,
+                    })
+                })
+                .ok()
+        })
+        .collect::<BagGraph>();
+
+    if !errors.is_empty() {
+        for err in &errors {
+            eprintln!("{err}");
+        }
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Failed to parse {} line(s) of bag rules", errors.len()),
+        ));
+    }
+
+    // Rules are exported before they are validated so that cycles can be inspected
+    if let Some(subgraph) = &options.dot {
+        return dot::export(&mut io::stdout().lock(), &bags, subgraph);
+    }
+    if let Some(order) = options.print {
+        return pretty::print(&mut io::stdout().lock(), &bags, order);
+    }
+
+    if let Err(errors) = bags.validate() {
+        for err in &errors {
+            eprintln!("{err}");
+        }
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Found {} problem(s) with the bag rules", errors.len()),
+        ));
+    }
+
+    if options.analyze {
+        analysis::print_analysis(&bags);
+        return Ok(());
+    }
+
+    if options.colors.is_empty() {
+        part_one(&bags)?;
+        part_two(&bags)?;
+    }
+
+    for color in &options.colors {
+        let containers = bags.all_containers(color).map_err(unknown_color)?;
+        let contents = bags.total_contents(color).map_err(count_failed)?;
+        println!(
+            "{color}: can be inside {} other bag colour(s), contains {contents} bag(s)",
+            containers.len()
+        );
+    }
 
-    let bags = input.lines().map(|line| {
-        let mut definition = RuleParser::parse(Rule::definition, line)
-            .expect("Failed to parse bag definition")
-            .next()
-            .unwrap()
-            .into_inner();
-        let parent = definition
-            .next()
-            .unwrap()
-            .into_inner()
-            .next()
-            .unwrap()
-            .as_str();
-        let children = definition.next().unwrap().into_inner();
-        (parent, children)
-    });
-
-    part_one(bags.clone());
-    part_two(bags);
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn parse(line: &str) -> (&str, Vec<BagRule<'_>>) {
+        parse_definition(line).unwrap()
+    }
+
+    #[test]
+    fn test_multi_word_colors() {
+        assert_eq!(
+            parse("pale dark shiny gold bags contain 1 very faded blue bag, 2 red bags."),
+            (
+                "pale dark shiny gold",
+                vec![
+                    BagRule {
+                        quantity: 1,
+                        bag: "very faded blue",
+                    },
+                    BagRule {
+                        quantity: 2,
+                        bag: "red",
+                    },
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn test_uppercase() {
+        assert_eq!(
+            parse("Shiny Gold BAGS CONTAIN 2 Dark Red Bags."),
+            (
+                "Shiny Gold",
+                vec![BagRule {
+                    quantity: 2,
+                    bag: "Dark Red",
+                }]
+            )
+        );
+        assert_eq!(
+            parse("Faded Blue Bags Contain No Other Bags."),
+            ("Faded Blue", vec![])
+        );
+    }
+
+    #[test]
+    fn test_trailing_whitespace() {
+        assert_eq!(
+            parse("faded blue bags contain no other bags. \t "),
+            ("faded blue", vec![])
+        );
+        assert!(
+            RuleParser::parse(Rule::definition, "faded blue bags contain no other bags.\t").is_ok()
+        );
+    }
+
+    #[test]
+    fn test_missing_period() {
+        assert_eq!(
+            parse("shiny gold bags contain 1 dark red bag"),
+            (
+                "shiny gold",
+                vec![BagRule {
+                    quantity: 1,
+                    bag: "dark red",
+                }]
+            )
+        );
+        assert_eq!(
+            parse("faded blue bags contain no other bags"),
+            ("faded blue", vec![])
+        );
+    }
+
+    #[test]
+    fn test_wrong_plurals() {
+        for line in [
+            "shiny gold bags contain 1 dark red bags.",
+            "shiny gold bags contain 2 dark red bag.",
+            "shiny gold bags contain 3 dark red bags, 1 faded blue bags.",
+        ] {
+            assert!(
+                RuleParser::parse(Rule::definition, line).is_err(),
+                "`{line}` should not parse"
+            );
+        }
+    }
+}
@@ -0,0 +1,142 @@
+//! Writes bag rules back out as the text they were parsed from
+
+use crate::graph::{BagGraph, BagRule};
+use std::fmt;
+use std::io::{self, Write};
+use std::str::FromStr;
+
+/// The order rules, and the bags within each rule, are printed in
+#[derive(Clone, Copy)]
+pub enum Order {
+    /// The order they were given in
+    Original,
+    /// Alphabetical order of their colours
+    Sorted,
+}
+
+#[derive(Debug)]
+pub struct ParseOrderError(String);
+
+impl FromStr for Order {
+    type Err = ParseOrderError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "original" => Ok(Self::Original),
+            "sorted" => Ok(Self::Sorted),
+            _ => Err(ParseOrderError(s.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for ParseOrderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Unknown order `{}`, expected `original` or `sorted`",
+            self.0
+        )
+    }
+}
+
+/// `quantity` bags of `color`, using `bag` rather than `bags` for exactly one like `singular_rule`
+/// in `grammar.pest` requires
+fn bags(quantity: u32, color: &str) -> String {
+    if quantity == 1 {
+        format!("1 {color} bag")
+    } else {
+        format!("{quantity} {color} bags")
+    }
+}
+
+/// The rule defining what a `color` bag contains
+fn definition(color: &str, contents: &[BagRule<'_>]) -> String {
+    if contents.is_empty() {
+        return format!("{color} bags contain no other bags.");
+    }
+
+    let contents = contents
+        .iter()
+        .map(|rule| bags(rule.quantity, rule.bag))
+        .collect::<Vec<_>>();
+    format!("{color} bags contain {}.", contents.join(", "))
+}
+
+/// Writes every rule of `bags` to `out`, one per line, in `order`
+pub fn print<W: Write>(out: &mut W, bags: &BagGraph<'_>, order: Order) -> io::Result<()> {
+    let mut colors = bags.defined().to_vec();
+    if let Order::Sorted = order {
+        colors.sort_unstable();
+    }
+
+    for color in colors {
+        let mut contents = bags.contents(color).unwrap_or_default().to_vec();
+        if let Order::Sorted = order {
+            contents.sort_unstable_by_key(|rule| rule.bag);
+        }
+
+        writeln!(out, "{}", definition(color, &contents))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parse_rules;
+
+    const RULES: &str = "\
+light red bags contain 1 bright white bag, 2 muted yellow bags.
+dark orange bags contain 3 bright white bags, 4 muted yellow bags.
+bright white bags contain 1 shiny gold bag.
+muted yellow bags contain 9 faded blue bags, 2 shiny gold bags.
+shiny gold bags contain 2 vibrant plum bags, 1 dark olive bag.
+dark olive bags contain 3 faded blue bags, 4 dotted black bags.
+vibrant plum bags contain 5 faded blue bags, 6 dotted black bags.
+faded blue bags contain no other bags.
+dotted black bags contain no other bags.";
+
+    /// The contents of every defined colour in the order they were defined
+    fn contents<'a>(bags: &BagGraph<'a>) -> Vec<(&'a str, Vec<BagRule<'a>>)> {
+        bags.defined()
+            .iter()
+            .map(|color| (*color, bags.contents(color).unwrap().to_vec()))
+            .collect()
+    }
+
+    /// `bags` printed in `order`
+    fn printed(bags: &BagGraph<'_>, order: Order) -> String {
+        let mut out = Vec::new();
+        print(&mut out, bags, order).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_round_trip_original() {
+        let bags = parse_rules(RULES);
+        let printed = printed(&bags, Order::Original);
+
+        assert_eq!(contents(&parse_rules(&printed)), contents(&bags));
+    }
+
+    #[test]
+    fn test_round_trip_sorted() {
+        let bags = parse_rules(RULES);
+        let printed = printed(&bags, Order::Sorted);
+
+        let mut expected = contents(&bags);
+        for (_, contents) in &mut expected {
+            contents.sort_unstable_by_key(|rule| rule.bag);
+        }
+        expected.sort_unstable_by_key(|(color, _)| *color);
+        assert_eq!(contents(&parse_rules(&printed)), expected);
+    }
+
+    #[test]
+    fn test_original_order() {
+        let printed = printed(&parse_rules(RULES), Order::Original);
+
+        assert_eq!(printed.trim_end(), RULES);
+    }
+}
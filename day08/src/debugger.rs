@@ -0,0 +1,554 @@
+//! An interactive debugger that steps an `Executor` through a `Tape` one command at a time
+
+use crate::{Executor, Operation, Tape};
+use std::fmt;
+use std::io::{self, BufRead, Write};
+use std::str::FromStr;
+
+const HELP: &str = "\
+Commands:
+  step [n]          Run the next n instructions (default 1)
+  continue          Run until a breakpoint or watch stops execution
+  break <address>   Stop before running the instruction at an address
+  break <op>        Stop before running any `acc`, `jmp` or `nop` instruction
+  watch <cmp> <n>   Stop once the accumulator becomes ==, !=, <, <=, > or >= n
+  delete            Remove every breakpoint and watch
+  list [radius]     Print the instructions around the head (default 3 either side)
+  info              Print the head, accumulator, breakpoints and watches
+  reset             Start the program again, keeping breakpoints and watches
+  help              Print this message
+  quit              Leave the debugger
+An empty line repeats the previous command.";
+
+/// Where execution stops before running an instruction
+#[derive(Clone)]
+enum Breakpoint {
+    Address(usize),
+    Operation(Operation),
+}
+
+#[derive(Clone, Copy)]
+enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+/// A condition on the accumulator that stops execution when it becomes true
+#[derive(Clone)]
+struct Watch {
+    comparison: Comparison,
+    value: i32,
+}
+
+#[derive(Clone)]
+enum Command {
+    Step(usize),
+    Continue,
+    Break(Breakpoint),
+    Watch(Watch),
+    Delete,
+    List(usize),
+    Info,
+    Reset,
+    Help,
+    Quit,
+}
+
+#[derive(Debug)]
+struct ParseCommandError(String);
+
+/// Why execution stopped before a command finished running every instruction it asked for
+enum Stop {
+    /// The index of the breakpoint that was hit
+    Breakpoint(usize),
+    /// The index of the watch whose condition became true
+    Watch(usize),
+    /// The next instruction has already been run once
+    Looping,
+    /// The next instruction jumps to an address outside the range of `usize`
+    OutOfBounds,
+    /// The head has moved past the last instruction
+    Terminated,
+}
+
+pub struct Debugger<'a> {
+    tape: &'a Tape,
+    executor: Executor<'a>,
+    breakpoints: Vec<Breakpoint>,
+    watches: Vec<Watch>,
+    /// Whether a breakpoint on the first instruction has had its chance to stop execution since
+    /// the program last started
+    started: bool,
+}
+
+impl FromStr for Breakpoint {
+    type Err = ParseCommandError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse()
+            .map(Self::Operation)
+            .or_else(|_| s.parse().map(Self::Address))
+            .map_err(|_| {
+                ParseCommandError(format!(
+                    "Expected an address, `acc`, `jmp` or `nop`, found `{s}`"
+                ))
+            })
+    }
+}
+
+impl fmt::Display for Breakpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Address(address) => write!(f, "address {address}"),
+            Self::Operation(operation) => write!(f, "every `{operation}`"),
+        }
+    }
+}
+
+impl FromStr for Comparison {
+    type Err = ParseCommandError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "==" => Ok(Self::Equal),
+            "!=" => Ok(Self::NotEqual),
+            "<" => Ok(Self::Less),
+            "<=" => Ok(Self::LessOrEqual),
+            ">" => Ok(Self::Greater),
+            ">=" => Ok(Self::GreaterOrEqual),
+            _ => Err(ParseCommandError(format!(
+                "Expected one of ==, !=, <, <=, > or >=, found `{s}`"
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbol = match self {
+            Self::Equal => "==",
+            Self::NotEqual => "!=",
+            Self::Less => "<",
+            Self::LessOrEqual => "<=",
+            Self::Greater => ">",
+            Self::GreaterOrEqual => ">=",
+        };
+        write!(f, "{symbol}")
+    }
+}
+
+impl Watch {
+    fn holds(&self, accumulator: i32) -> bool {
+        match self.comparison {
+            Comparison::Equal => accumulator == self.value,
+            Comparison::NotEqual => accumulator != self.value,
+            Comparison::Less => accumulator < self.value,
+            Comparison::LessOrEqual => accumulator <= self.value,
+            Comparison::Greater => accumulator > self.value,
+            Comparison::GreaterOrEqual => accumulator >= self.value,
+        }
+    }
+}
+
+impl fmt::Display for Watch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "accumulator {} {}", self.comparison, self.value)
+    }
+}
+
+impl FromStr for Command {
+    type Err = ParseCommandError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let words = s.split_whitespace().collect::<Vec<_>>();
+        let count = |word: Option<&&str>, default| {
+            word.map_or(Ok(default), |word| {
+                word.parse().map_err(|_| {
+                    ParseCommandError(format!("Expected a positive number, found `{word}`"))
+                })
+            })
+        };
+
+        match words.as_slice() {
+            ["step" | "s", rest @ ..] if rest.len() <= 1 => count(rest.first(), 1).map(Self::Step),
+            ["continue" | "c"] => Ok(Self::Continue),
+            ["break" | "b", breakpoint] => breakpoint.parse().map(Self::Break),
+            ["watch" | "w", comparison, value] => Ok(Self::Watch(Watch {
+                comparison: comparison.parse()?,
+                value: value.parse().map_err(|_| {
+                    ParseCommandError(format!("Expected an accumulator value, found `{value}`"))
+                })?,
+            })),
+            ["delete" | "d"] => Ok(Self::Delete),
+            ["list" | "l", rest @ ..] if rest.len() <= 1 => count(rest.first(), 3).map(Self::List),
+            ["info" | "i"] => Ok(Self::Info),
+            ["reset" | "r"] => Ok(Self::Reset),
+            ["help" | "h"] => Ok(Self::Help),
+            ["quit" | "q"] => Ok(Self::Quit),
+            _ => Err(ParseCommandError(format!(
+                "Unknown command `{s}`, try `help`"
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for ParseCommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl<'a> Debugger<'a> {
+    pub fn new(tape: &'a Tape) -> Self {
+        Self {
+            tape,
+            executor: Executor::from(tape),
+            breakpoints: Vec::new(),
+            watches: Vec::new(),
+            started: false,
+        }
+    }
+
+    /// Reads commands from `input` until it runs out or a `quit` command, writing what happens to
+    /// `out`
+    pub fn run<R: BufRead, W: Write>(&mut self, input: R, out: &mut W) -> io::Result<()> {
+        let mut previous = None;
+        let mut lines = input.lines();
+
+        loop {
+            write!(out, "(debug) ")?;
+            out.flush()?;
+            let Some(line) = lines.next().transpose()? else {
+                writeln!(out)?;
+                return Ok(());
+            };
+
+            let command = if line.trim().is_empty() {
+                match previous.clone() {
+                    Some(command) => command,
+                    None => continue,
+                }
+            } else {
+                match line.parse::<Command>() {
+                    Ok(command) => command,
+                    Err(err) => {
+                        writeln!(out, "{err}")?;
+                        continue;
+                    }
+                }
+            };
+
+            if let Command::Quit = command {
+                return Ok(());
+            }
+            self.execute(&command, out)?;
+            previous = Some(command);
+        }
+    }
+
+    fn execute<W: Write>(&mut self, command: &Command, out: &mut W) -> io::Result<()> {
+        match command {
+            Command::Step(count) => {
+                let stop = (0..*count).find_map(|_| self.advance());
+                self.report(stop, out)?;
+            }
+            Command::Continue => {
+                let stop = std::iter::repeat_with(|| self.advance()).find_map(|stop| stop);
+                self.report(stop, out)?;
+            }
+            Command::Break(breakpoint) => {
+                self.breakpoints.push(breakpoint.clone());
+                writeln!(out, "Breakpoint {} at {breakpoint}", self.breakpoints.len())?;
+            }
+            Command::Watch(watch) => {
+                self.watches.push(watch.clone());
+                writeln!(out, "Watch {} on {watch}", self.watches.len())?;
+            }
+            Command::Delete => {
+                self.breakpoints.clear();
+                self.watches.clear();
+                writeln!(out, "Deleted every breakpoint and watch")?;
+            }
+            Command::List(radius) => self.list(*radius, out)?,
+            Command::Info => {
+                self.print_state(out)?;
+                for (i, breakpoint) in self.breakpoints.iter().enumerate() {
+                    writeln!(out, "Breakpoint {} at {breakpoint}", i + 1)?;
+                }
+                for (i, watch) in self.watches.iter().enumerate() {
+                    writeln!(out, "Watch {} on {watch}", i + 1)?;
+                }
+            }
+            Command::Reset => {
+                self.executor = Executor::from(self.tape);
+                self.started = false;
+                self.print_state(out)?;
+            }
+            Command::Help => writeln!(out, "{HELP}")?,
+            Command::Quit => {}
+        }
+
+        Ok(())
+    }
+
+    /// Runs the instruction under the head, returning why execution should stop, if it should
+    fn advance(&mut self) -> Option<Stop> {
+        if !self.started {
+            self.started = true;
+            if let Some(i) = self.breakpoint_at_head() {
+                return Some(Stop::Breakpoint(i));
+            }
+        }
+        if self.executor.is_looping() {
+            return Some(Stop::Looping);
+        }
+
+        let before = self.executor.accumulator;
+        if self.executor.next().is_none() {
+            return Some(if self.executor.out_of_bounds {
+                Stop::OutOfBounds
+            } else {
+                Stop::Terminated
+            });
+        }
+        let after = self.executor.accumulator;
+
+        if let Some(i) = self
+            .watches
+            .iter()
+            .position(|watch| !watch.holds(before) && watch.holds(after))
+        {
+            return Some(Stop::Watch(i));
+        }
+        if self.executor.head >= self.tape.len() {
+            return Some(Stop::Terminated);
+        }
+
+        self.breakpoint_at_head().map(Stop::Breakpoint)
+    }
+
+    /// The index of the first breakpoint on the instruction under the head
+    fn breakpoint_at_head(&self) -> Option<usize> {
+        let head = self.executor.head;
+        let instruction = self.tape.get(head)?;
+        self.breakpoints
+            .iter()
+            .position(|breakpoint| match breakpoint {
+                Breakpoint::Address(address) => *address == head,
+                Breakpoint::Operation(operation) => *operation == instruction.operation(),
+            })
+    }
+
+    fn report<W: Write>(&self, stop: Option<Stop>, out: &mut W) -> io::Result<()> {
+        match stop {
+            Some(Stop::Breakpoint(i)) => writeln!(
+                out,
+                "Stopped at breakpoint {} ({})",
+                i + 1,
+                self.breakpoints[i]
+            )?,
+            Some(Stop::Watch(i)) => {
+                writeln!(out, "Stopped by watch {} ({})", i + 1, self.watches[i])?
+            }
+            Some(Stop::Looping) => writeln!(
+                out,
+                "Stopped before running instruction {} a second time",
+                self.executor.head
+            )?,
+            Some(Stop::OutOfBounds) => writeln!(
+                out,
+                "Stopped before jumping outside the program from instruction {}",
+                self.executor.head
+            )?,
+            Some(Stop::Terminated) => writeln!(out, "Program terminated")?,
+            None => {}
+        }
+
+        self.print_state(out)
+    }
+
+    fn print_state<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        let head = self.executor.head;
+        match self.tape.get(head) {
+            Some(instruction) => write!(out, "{head}: {instruction}")?,
+            None => write!(out, "{head}: <end of program>")?,
+        }
+        writeln!(out, " (accumulator {})", self.executor.accumulator)
+    }
+
+    /// Prints the instructions within `radius` of the head, marking the head with `=>`, address
+    /// breakpoints with `*` and instructions that have already run with `+`
+    fn list<W: Write>(&self, radius: usize, out: &mut W) -> io::Result<()> {
+        let head = self.executor.head;
+        let end = head
+            .saturating_add(radius)
+            .saturating_add(1)
+            .min(self.tape.len());
+
+        for address in head.saturating_sub(radius)..end {
+            let cursor = if address == head { "=>" } else { "  " };
+            let breakpoint = self
+                .breakpoints
+                .iter()
+                .any(|breakpoint| matches!(breakpoint, Breakpoint::Address(a) if *a == address));
+            let executed = self.executor.executed[address].is_some();
+            writeln!(
+                out,
+                "{cursor}{}{} {address:>4}: {}",
+                if breakpoint { '*' } else { ' ' },
+                if executed { '+' } else { ' ' },
+                self.tape[address]
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parse_tape;
+
+    const PROGRAM: &str = "\
+nop +0
+acc +1
+jmp +4
+acc +3
+jmp -3
+acc -99
+acc +1
+jmp -4
+acc +6";
+
+    /// Everything the debugger writes while running `commands` against `program`, without prompts
+    fn session(program: &str, commands: &str) -> Vec<String> {
+        let tape = parse_tape(program).unwrap();
+        let mut out = Vec::new();
+        Debugger::new(&tape)
+            .run(commands.as_bytes(), &mut out)
+            .unwrap();
+
+        String::from_utf8(out)
+            .unwrap()
+            .replace("(debug) ", "")
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn test_step() {
+        assert_eq!(
+            session(PROGRAM, "step 3\nstep\n\n"),
+            [
+                "6: acc +1 (accumulator 1)",
+                "7: jmp -4 (accumulator 2)",
+                "3: acc +3 (accumulator 2)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_address_breakpoint() {
+        assert_eq!(
+            session(PROGRAM, "break 4\ncontinue\ncontinue\n"),
+            [
+                "Breakpoint 1 at address 4",
+                "Stopped at breakpoint 1 (address 4)",
+                "4: jmp -3 (accumulator 5)",
+                "Stopped before running instruction 1 a second time",
+                "1: acc +1 (accumulator 5)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_breakpoint_at_start() {
+        assert_eq!(
+            session(PROGRAM, "break 0\ncontinue\nstep\nreset\ncontinue\n"),
+            [
+                "Breakpoint 1 at address 0",
+                "Stopped at breakpoint 1 (address 0)",
+                "0: nop +0 (accumulator 0)",
+                "1: acc +1 (accumulator 0)",
+                "0: nop +0 (accumulator 0)",
+                "Stopped at breakpoint 1 (address 0)",
+                "0: nop +0 (accumulator 0)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_operation_breakpoints() {
+        assert_eq!(
+            session(PROGRAM, "break jmp\ncontinue\ncontinue\n"),
+            [
+                "Breakpoint 1 at every `jmp`",
+                "Stopped at breakpoint 1 (every `jmp`)",
+                "2: jmp +4 (accumulator 1)",
+                "Stopped at breakpoint 1 (every `jmp`)",
+                "7: jmp -4 (accumulator 2)",
+            ]
+        );
+        assert_eq!(
+            session(PROGRAM, "break acc\nbreak nop\ncontinue\nc\n"),
+            [
+                "Breakpoint 1 at every `acc`",
+                "Breakpoint 2 at every `nop`",
+                "Stopped at breakpoint 2 (every `nop`)",
+                "0: nop +0 (accumulator 0)",
+                "Stopped at breakpoint 1 (every `acc`)",
+                "1: acc +1 (accumulator 0)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_watch() {
+        assert_eq!(
+            session(PROGRAM, "watch >= 5\ncontinue\n"),
+            [
+                "Watch 1 on accumulator >= 5",
+                "Stopped by watch 1 (accumulator >= 5)",
+                "4: jmp -3 (accumulator 5)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_list() {
+        assert_eq!(
+            session(PROGRAM, "break 3\nstep 2\nlist 1\n"),
+            [
+                "Breakpoint 1 at address 3",
+                "2: jmp +4 (accumulator 1)",
+                "   +    1: acc +1",
+                "=>      2: jmp +4",
+                "  *     3: acc +3",
+            ]
+        );
+        assert_eq!(
+            session(PROGRAM, "list 18446744073709551615\n").len(),
+            PROGRAM.lines().count()
+        );
+    }
+
+    #[test]
+    fn test_jump_out_of_bounds() {
+        assert_eq!(
+            session("acc +2\njmp -5", "continue\nstep\n"),
+            [
+                "Stopped before jumping outside the program from instruction 1",
+                "1: jmp -5 (accumulator 2)",
+                "Stopped before jumping outside the program from instruction 1",
+                "1: jmp -5 (accumulator 2)",
+            ]
+        );
+    }
+}
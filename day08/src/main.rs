@@ -1,10 +1,21 @@
+mod debugger;
+
 use std::{
+    env, fmt, fs,
     io::{self, Read},
     num::ParseIntError,
     ops::{Deref, DerefMut},
     str::FromStr,
 };
 
+/// The kind of an `Instruction`, regardless of its argument
+#[derive(Clone, Copy, PartialEq)]
+enum Operation {
+    Acc,
+    Jmp,
+    Nop,
+}
+
 #[derive(Clone, Copy)]
 enum Instruction {
     Acc(i32),
@@ -26,6 +37,9 @@ struct Executor<'a> {
     /// A vector where element `i` holds an `Option` indicating whether `tape[i]` has been executed
     executed: Vec<Option<Instruction>>,
     head: usize,
+    /// Whether the instruction under `head` is a jump to an address before `0` or past `usize::MAX`,
+    /// which stops execution without running it
+    out_of_bounds: bool,
     tape: &'a Tape,
 }
 
@@ -35,21 +49,74 @@ impl From<ParseIntError> for ParseInstructionError {
     }
 }
 
+impl FromStr for Operation {
+    type Err = ParseInstructionError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "acc" => Ok(Self::Acc),
+            "jmp" => Ok(Self::Jmp),
+            "nop" => Ok(Self::Nop),
+            _ => Err(Self::Err::InvalidOperation),
+        }
+    }
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Acc => "acc",
+            Self::Jmp => "jmp",
+            Self::Nop => "nop",
+        };
+        write!(f, "{name}")
+    }
+}
+
 impl FromStr for Instruction {
     type Err = ParseInstructionError;
 
     fn from_str(value: &str) -> Result<Self, Self::Err> {
-        match value.split_once(' ') {
-            Some(("acc", arg)) => Ok(Self::Acc(arg.parse()?)),
-            Some(("jmp", arg)) => Ok(Self::Jmp(arg.parse()?)),
-            Some(("nop", arg)) => Ok(Self::Nop(arg.parse()?)),
-            Some((_, _)) => Err(Self::Err::InvalidOperation),
-            None => Err(Self::Err::IncompleteInstruction),
+        let (operation, arg) = value
+            .split_once(' ')
+            .ok_or(Self::Err::IncompleteInstruction)?;
+
+        match operation.parse()? {
+            Operation::Acc => Ok(Self::Acc(arg.parse()?)),
+            Operation::Jmp => Ok(Self::Jmp(arg.parse()?)),
+            Operation::Nop => Ok(Self::Nop(arg.parse()?)),
+        }
+    }
+}
+
+impl fmt::Display for ParseInstructionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::IncompleteInstruction => write!(f, "Expected an operation and an argument"),
+            Self::InvalidOperation => write!(f, "Expected `acc`, `jmp` or `nop`"),
+            Self::InvalidArgument => write!(f, "Expected a signed integer argument"),
+        }
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Acc(arg) => write!(f, "{} {arg:+}", self.operation()),
+            Self::Jmp(arg) | Self::Nop(arg) => write!(f, "{} {arg:+}", self.operation()),
         }
     }
 }
 
 impl Instruction {
+    fn operation(&self) -> Operation {
+        match self {
+            Self::Acc(_) => Operation::Acc,
+            Self::Jmp(_) => Operation::Jmp,
+            Self::Nop(_) => Operation::Nop,
+        }
+    }
+
     fn invert(&mut self) {
         match self {
             Self::Jmp(arg) => {
@@ -96,6 +163,7 @@ impl<'a> From<&'a Tape> for Executor<'a> {
         Self {
             accumulator: 0,
             head: 0,
+            out_of_bounds: false,
             tape,
             executed: tape.iter().map(|_| None).collect(),
         }
@@ -119,13 +187,13 @@ impl<'a> Iterator for Executor<'a> {
                             self.accumulator += arg;
                             self.head += 1;
                         }
-                        Jmp(arg) => {
-                            if arg.is_negative() {
-                                self.head -= arg.wrapping_abs() as usize;
-                            } else {
-                                self.head += *arg as usize;
+                        Jmp(arg) => match self.head.checked_add_signed(*arg) {
+                            Some(head) => self.head = head,
+                            None => {
+                                self.out_of_bounds = true;
+                                return None;
                             }
-                        }
+                        },
                         Nop(_) => {
                             self.head += 1;
                         }
@@ -167,7 +235,7 @@ fn part_two<I: Iterator<Item = Instruction>>(instructions: I) {
                 tape.get_mut(ptr).unwrap().invert();
                 let executor = tape.evaluate();
 
-                if executor.is_looping() {
+                if executor.is_looping() || executor.out_of_bounds {
                     tape.get_mut(ptr).unwrap().invert();
                     None
                 } else {
@@ -180,7 +248,36 @@ fn part_two<I: Iterator<Item = Instruction>>(instructions: I) {
     println!("Part Two: {answer:?}");
 }
 
+/// Parses a program from `input`, one instruction per line
+fn parse_tape(input: &str) -> io::Result<Tape> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(i, instruction)| {
+            instruction.parse().map_err(|err| {
+                io::Error::new(io::ErrorKind::InvalidData, format!("Line {}: {err}", i + 1))
+            })
+        })
+        .collect()
+}
+
 fn main() -> io::Result<()> {
+    // `--debug <program>` reads the program from a file, leaving stdin for debugger commands
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    match args.as_slice() {
+        [] => {}
+        [flag, path] if flag == "--debug" => {
+            let tape = parse_tape(&fs::read_to_string(path)?)?;
+            return debugger::Debugger::new(&tape).run(io::stdin().lock(), &mut io::stdout());
+        }
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Expected no arguments or `--debug <program>`",
+            ))
+        }
+    }
+
     let mut input = String::new();
     io::stdin().read_to_string(&mut input)?;
 
@@ -0,0 +1,81 @@
+//! Splits input into records separated by one or more blank lines, tolerating CRLF line endings and
+//! lines holding nothing but whitespace
+
+/// An iterator over the records of some input, yielding the 1-indexed line each record starts on
+/// alongside the record itself, trimmed of leading and trailing blank lines and whitespace
+pub struct Records<'a> {
+    input: &'a str,
+    /// The byte offset of the next unread line of `input`
+    offset: usize,
+    /// The 1-indexed number of the next unread line of `input`
+    line: usize,
+}
+
+pub fn records(input: &str) -> Records<'_> {
+    Records {
+        input,
+        offset: 0,
+        line: 1,
+    }
+}
+
+impl<'a> Iterator for Records<'a> {
+    type Item = (usize, &'a str);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut start = None;
+        let mut end = self.offset;
+
+        while self.offset < self.input.len() {
+            let rest = &self.input[self.offset..];
+            let line = &rest[..rest.find('\n').map_or(rest.len(), |i| i + 1)];
+            let (line_offset, line_number) = (self.offset, self.line);
+
+            if line.trim().is_empty() && start.is_some() {
+                break;
+            }
+
+            self.offset += line.len();
+            self.line += 1;
+
+            if !line.trim().is_empty() {
+                let indent = line.len() - line.trim_start().len();
+                start.get_or_insert((line_offset + indent, line_number));
+                end = line_offset + line.trim_end().len();
+            }
+        }
+
+        start.map(|(start, line_number)| (line_number, &self.input[start..end]))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_crlf() {
+        assert!(records("a b\r\nc\r\n\r\nd\r\n").eq([(1, "a b\r\nc"), (4, "d")]));
+    }
+
+    #[test]
+    fn test_whitespace_separators() {
+        assert!(records("a\n \t\nb\n  \r\nc").eq([(1, "a"), (3, "b"), (5, "c")]));
+    }
+
+    #[test]
+    fn test_many_blank_lines() {
+        assert!(records("\n\na\n\n\n\nb\n\n\n").eq([(3, "a"), (7, "b")]));
+    }
+
+    #[test]
+    fn test_indentation() {
+        assert!(records("  a\n    b\n\n\tc  \n").eq([(1, "a\n    b"), (4, "c")]));
+    }
+
+    #[test]
+    fn test_empty() {
+        assert_eq!(records("").next(), None);
+        assert_eq!(records(" \n\r\n\t\n").next(), None);
+    }
+}